@@ -1,5 +1,7 @@
 mod device;
+mod ring_buffer;
 mod stream;
 
 pub use device::{find_device_by_name, get_input_device_names};
+pub use ring_buffer::RingConsumer;
 pub use stream::start_audio_stream;
@@ -0,0 +1,305 @@
+use std::sync::atomic::{fence, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+/// 入力コールバックと解析スレッドの間で共有するロックフリーのリングバッファ（単一の書き込み側・単一の読み出し側）
+/// サンプルはf32のビット列としてアトミックに格納し、書き込み位置（累計サンプル数）の公開で読み出し側に渡す
+/// 書き込み前に書き込み予定の終端を公開し、読み出し側は読み出し後にそれを見て上書き中の範囲を捨てる
+struct RingBuffer {
+    /// サンプル（インターリーブ、f32::to_bits）
+    samples: Box<[AtomicU32]>,
+    /// 1フレームあたりのチャンネル数
+    channels: usize,
+    /// 累計の書き込みサンプル数（ここまでのサンプルが読み出し可能）
+    write_pos: AtomicU64,
+    /// 書き込み中（または書き込み済み）の終端（この位置から容量分より前のスロットは上書きされうる）
+    write_intent: AtomicU64,
+    /// 読み出し側が待っている書き込み位置（待っていなければu64::MAX）
+    wake_at: AtomicU64,
+    /// 待機する読み出し側のスレッド（最初の待機時に登録）
+    waiter: OnceLock<Thread>,
+    /// 最後に書き込んだ時刻（created からの経過マイクロ秒）
+    last_write_micros: AtomicU64,
+    /// 生成時刻（時刻の基準）
+    created: Instant,
+}
+
+/// 書き込み側（オーディオコールバック用、ロック・アロケーションなし）
+pub struct RingProducer {
+    ring: Arc<RingBuffer>,
+}
+
+/// 読み出し側（解析スレッド用）
+pub struct RingConsumer {
+    ring: Arc<RingBuffer>,
+    /// 次に読み出すサンプル位置
+    read_pos: u64,
+}
+
+/// 読み出し結果
+#[derive(Debug, Clone, Copy)]
+pub struct ReadInfo {
+    /// 読み出したフレーム数
+    pub frames: usize,
+    /// 書き込み側に追い越されて失ったフレーム数
+    pub dropped_frames: usize,
+    /// 読み出した範囲の終端（ストリーム開始からの累計フレーム数）
+    pub end_frame: u64,
+    /// 終端のフレームが書き込まれた時刻
+    pub timestamp: Instant,
+}
+
+/// 指定フレーム数を保持できるリングバッファを作成
+pub fn ring_buffer(frames: usize, channels: usize) -> (RingProducer, RingConsumer) {
+    let channels = channels.max(1);
+    let ring = Arc::new(RingBuffer {
+        samples: (0..frames.max(1) * channels)
+            .map(|_| AtomicU32::new(0))
+            .collect(),
+        channels,
+        write_pos: AtomicU64::new(0),
+        write_intent: AtomicU64::new(0),
+        wake_at: AtomicU64::new(u64::MAX),
+        waiter: OnceLock::new(),
+        last_write_micros: AtomicU64::new(0),
+        created: Instant::now(),
+    });
+    (
+        RingProducer { ring: ring.clone() },
+        RingConsumer { ring, read_pos: 0 },
+    )
+}
+
+impl RingBuffer {
+    fn capacity(&self) -> u64 {
+        self.samples.len() as u64
+    }
+
+    fn slot(&self, pos: u64) -> &AtomicU32 {
+        &self.samples[(pos % self.capacity()) as usize]
+    }
+}
+
+impl RingProducer {
+    /// インターリーブされたサンプルを書き込む（古いサンプルは上書き）
+    pub fn push(&mut self, data: &[f32]) {
+        let ring = &self.ring;
+        // 書き込み位置を更新するのはこのスレッドだけ
+        let pos = ring.write_pos.load(Ordering::Relaxed);
+        let end = pos + data.len() as u64;
+        // スロットを上書きする前に書き込み予定の終端を公開する
+        // （読み出し側が上書き後の値を読んだなら、このfenceによりwrite_intentの更新も見える）
+        ring.write_intent.store(end, Ordering::Relaxed);
+        fence(Ordering::Release);
+        let skip = data.len().saturating_sub(ring.samples.len());
+        for (i, &v) in data.iter().enumerate().skip(skip) {
+            ring.slot(pos + i as u64)
+                .store(v.to_bits(), Ordering::Relaxed);
+        }
+        let micros = ring.created.elapsed().as_micros() as u64;
+        ring.last_write_micros.store(micros, Ordering::Relaxed);
+        ring.write_pos.store(end, Ordering::Release);
+
+        // 読み出し側が待っている位置に届いたら起こす（書き込み位置の公開を待機位置の確認より前に順序付ける）
+        fence(Ordering::SeqCst);
+        if end >= ring.wake_at.load(Ordering::Relaxed) {
+            if let Some(waiter) = ring.waiter.get() {
+                waiter.unpark();
+            }
+        }
+    }
+}
+
+impl RingConsumer {
+    /// 前回の読み出し以降に書き込まれたサンプルを out に追記
+    pub fn read_new(&mut self, out: &mut Vec<f32>) -> ReadInfo {
+        let ring = &self.ring;
+        let channels = ring.channels as u64;
+        let write = ring.write_pos.load(Ordering::Acquire);
+        let micros = ring.last_write_micros.load(Ordering::Relaxed);
+
+        // 容量を超えて遅れた分は既に上書きされている
+        let mut start = self.read_pos.max(write.saturating_sub(ring.capacity()));
+        let base = out.len();
+        out.extend(
+            (start..write).map(|pos| f32::from_bits(ring.slot(pos).load(Ordering::Relaxed))),
+        );
+
+        // 読み出し中に書き込み側が追い越した（上書き中を含む）分を捨てる（フレーム境界に揃える）
+        // スロットの読み出しをwrite_intentの確認より前に順序付ける
+        fence(Ordering::Acquire);
+        let overwritten = ring
+            .write_intent
+            .load(Ordering::Relaxed)
+            .saturating_sub(ring.capacity());
+        if overwritten > start {
+            let lost = ((overwritten - start).div_ceil(channels) * channels).min(write - start);
+            out.drain(base..base + lost as usize);
+            start += lost;
+        }
+
+        let dropped = start - self.read_pos;
+        self.read_pos = write;
+        ReadInfo {
+            frames: ((write - start) / channels) as usize,
+            dropped_frames: (dropped / channels) as usize,
+            end_frame: write / channels,
+            timestamp: ring.created + Duration::from_micros(micros),
+        }
+    }
+
+    /// 前回の読み出し以降に指定フレーム数が書き込まれるまで、最大timeoutだけ待つ
+    /// 読み出し側のスレッドから呼ぶ。届いていればtrue（タイムアウトならfalse）
+    pub fn wait_for_frames(&self, frames: usize, timeout: Duration) -> bool {
+        let ring = &self.ring;
+        let target = self.read_pos + (frames * ring.channels) as u64;
+        ring.waiter.get_or_init(thread::current);
+        ring.wake_at.store(target, Ordering::Relaxed);
+        // 待機位置の公開を書き込み位置の確認より前に順序付ける（書き込み側の起こし忘れを防ぐ）
+        fence(Ordering::SeqCst);
+        let deadline = Instant::now() + timeout;
+        let mut arrived = ring.write_pos.load(Ordering::Acquire) >= target;
+        while !arrived {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            thread::park_timeout(deadline - now);
+            arrived = ring.write_pos.load(Ordering::Acquire) >= target;
+        }
+        ring.wake_at.store(u64::MAX, Ordering::Relaxed);
+        arrived
+    }
+
+    /// バッファの容量（サンプル数）
+    pub fn capacity(&self) -> usize {
+        self.ring.samples.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_only_new_samples() {
+        let (mut producer, mut consumer) = ring_buffer(8, 2);
+        let mut out = Vec::new();
+
+        producer.push(&[1.0, 2.0, 3.0, 4.0]);
+        let info = consumer.read_new(&mut out);
+        assert_eq!(out, vec![1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(info.frames, 2);
+        assert_eq!(info.end_frame, 2);
+
+        out.clear();
+        assert_eq!(consumer.read_new(&mut out).frames, 0);
+        assert!(out.is_empty());
+
+        producer.push(&[5.0, 6.0]);
+        consumer.read_new(&mut out);
+        assert_eq!(out, vec![5.0, 6.0]);
+    }
+
+    #[test]
+    fn test_wraps_around() {
+        let (mut producer, mut consumer) = ring_buffer(3, 1);
+        let mut out = Vec::new();
+        producer.push(&[1.0, 2.0]);
+        consumer.read_new(&mut out);
+        producer.push(&[3.0, 4.0, 5.0]);
+        out.clear();
+        let info = consumer.read_new(&mut out);
+        assert_eq!(out, vec![3.0, 4.0, 5.0]);
+        assert_eq!(info.dropped_frames, 0);
+    }
+
+    #[test]
+    fn test_reports_dropped_frames_on_overrun() {
+        let (mut producer, mut consumer) = ring_buffer(4, 2);
+        let mut out = Vec::new();
+        for i in 0..6 {
+            producer.push(&[i as f32, -(i as f32)]);
+        }
+        let info = consumer.read_new(&mut out);
+        assert_eq!(info.frames, 4);
+        assert_eq!(info.dropped_frames, 2);
+        assert_eq!(info.end_frame, 6);
+        assert_eq!(out, vec![2.0, -2.0, 3.0, -3.0, 4.0, -4.0, 5.0, -5.0]);
+    }
+
+    #[test]
+    fn test_wait_for_frames_wakes_on_enough_frames() {
+        let (mut producer, mut consumer) = ring_buffer(64, 2);
+        let mut out = Vec::new();
+
+        // 届いていなければタイムアウトまで待ってfalse
+        let started = Instant::now();
+        assert!(!consumer.wait_for_frames(4, Duration::from_millis(20)));
+        assert!(started.elapsed() >= Duration::from_millis(20));
+
+        // 既に届いていれば待たない
+        producer.push(&[0.0; 8]);
+        assert!(consumer.wait_for_frames(4, Duration::ZERO));
+        consumer.read_new(&mut out);
+
+        // 別スレッドからの書き込みで、指定フレーム数が揃った時点で起きる
+        let writer = std::thread::spawn(move || {
+            for _ in 0..4 {
+                std::thread::sleep(Duration::from_millis(5));
+                producer.push(&[1.0, 1.0]);
+            }
+        });
+        assert!(consumer.wait_for_frames(4, Duration::from_secs(10)));
+        out.clear();
+        assert_eq!(consumer.read_new(&mut out).frames, 4);
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn test_lagging_reader_never_sees_torn_samples() {
+        // 各サンプルに累計位置を書き込み、遅れて読む側が古い値・上書き中の値を返さないことを確かめる
+        const CHANNELS: usize = 2;
+        const CHUNK: usize = 128;
+        const TOTAL: u64 = 1 << 21;
+        let (mut producer, mut consumer) = ring_buffer(256, CHANNELS);
+
+        let writer = std::thread::spawn(move || {
+            let mut pos = 0u64;
+            let mut chunk = [0.0f32; CHUNK];
+            while pos < TOTAL {
+                for (i, v) in chunk.iter_mut().enumerate() {
+                    *v = (pos + i as u64) as f32;
+                }
+                producer.push(&chunk);
+                pos += CHUNK as u64;
+            }
+        });
+
+        let mut out = Vec::new();
+        let mut frames = 0u64;
+        let mut dropped = 0u64;
+        let mut end_frame = 0;
+        let mut reads = 0u32;
+        while end_frame * (CHANNELS as u64) < TOTAL {
+            out.clear();
+            let info = consumer.read_new(&mut out);
+            assert_eq!(out.len(), info.frames * CHANNELS);
+            let first = info.end_frame * CHANNELS as u64 - out.len() as u64;
+            for (i, &v) in out.iter().enumerate() {
+                assert_eq!(v, (first + i as u64) as f32, "read {}", reads);
+            }
+            frames += info.frames as u64;
+            dropped += info.dropped_frames as u64;
+            end_frame = info.end_frame;
+            reads += 1;
+            // ときどき遅れて追い越されるようにする
+            if reads.is_multiple_of(7) {
+                std::thread::sleep(Duration::from_micros(50));
+            }
+        }
+        writer.join().unwrap();
+        assert_eq!(frames + dropped, TOTAL / CHANNELS as u64);
+    }
+}
@@ -1,18 +1,17 @@
 use cpal::traits::{DeviceTrait, StreamTrait};
-use std::sync::{Arc, Mutex};
 
+use crate::audio::ring_buffer::{ring_buffer, RingConsumer};
 use crate::constants::{FFT_SIZE, STREAM};
 
-/// オーディオ入力ストリームを開始し、バッファにデータを蓄積
-pub fn start_audio_stream(
-    device: &cpal::Device,
-) -> Result<(Arc<Mutex<Vec<f32>>>, usize, usize), String> {
+/// リングバッファの容量（FFT_SIZEの何倍のフレームを保持するか、解析の遅れに対する余裕）
+const RING_BUFFER_FFT_MULTIPLE: usize = 4;
+
+/// オーディオ入力ストリームを開始し、リングバッファにデータを蓄積
+pub fn start_audio_stream(device: &cpal::Device) -> Result<(RingConsumer, usize, usize), String> {
     let config = device.default_input_config().map_err(|e| e.to_string())?;
     let sample_rate = config.sample_rate() as usize;
     let channels = config.channels() as usize;
-    let buffer_size = FFT_SIZE * channels;
-    let buffer = Arc::new(Mutex::new(Vec::<f32>::with_capacity(buffer_size)));
-    let buffer_clone = buffer.clone();
+    let (mut producer, consumer) = ring_buffer(FFT_SIZE * RING_BUFFER_FFT_MULTIPLE, channels);
 
     println!(
         "Audio config: sample_rate={}, channels={}, fft_size={}",
@@ -24,14 +23,8 @@ pub fn start_audio_stream(
     let stream = device
         .build_input_stream(
             &config.clone().into(),
-            move |data: &[f32], _| {
-                let mut buf = buffer_clone.lock().unwrap();
-                buf.extend_from_slice(data);
-                if buf.len() > buffer_size {
-                    let len = buf.len();
-                    buf.drain(..len - buffer_size);
-                }
-            },
+            // オーディオスレッドではロック・アロケーションをしない
+            move |data: &[f32], _| producer.push(data),
             err_fn,
             None,
         )
@@ -47,5 +40,5 @@ pub fn start_audio_stream(
         *global_stream = Some(stream);
     }
 
-    Ok((buffer, sample_rate, channels))
+    Ok((consumer, sample_rate, channels))
 }
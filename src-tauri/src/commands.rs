@@ -5,14 +5,36 @@ use std::sync::atomic::Ordering;
 use std::thread;
 use std::time::Duration;
 use tauri::command;
+use tauri::Emitter;
 use tauri::Manager;
 
 use crate::audio::{find_device_by_name, get_input_device_names, start_audio_stream};
 use crate::constants::{
-    CHANNEL_MODE, CUSTOM_PITCH, DROP_TUNING_ENABLED, DROP_TUNING_NOTE, LAST_TUNING_INFO, LOCALE,
-    PITCH_MODE, STOP_FLAG, STREAM_ID, THRESHOLD_RATIO, TRAY_ICON_MODE, TUNING_SHIFT,
+    invalidate_string_targets, TunerMode, TuningInfo, ACTIVE_OFFSET_TABLE, ACTIVE_TEMPERAMENT,
+    ACTIVE_TUNING, CAPO, CHANNEL_MODE, CUSTOM_PITCH, DROP_TUNING_ENABLED, DROP_TUNING_NOTE,
+    FFT_SIZE, HOP_SIZE, HUM_FILTER, INSTRUMENT, KAISER_BETA, KALMAN_MEASUREMENT_NOISE,
+    KALMAN_PROCESS_NOISE, LAST_TUNING_INFO, LOCALE, MAINS_FREQUENCY, MAX_ONSET_SKIP_MS,
+    MAX_THRESHOLD_RATIO, MIN_HOP_SIZE, MIN_THRESHOLD_RATIO, NOISE_CALIBRATION_DEFAULT_SECS,
+    NOISE_CALIBRATION_MAX_SECS, NOISE_CALIBRATION_MS, NOISE_GATE, NOISE_REDUCTION, ONSET_SKIP_MS,
+    PEAK_INTERPOLATOR, PEAK_REFINEMENT, PITCH_DETECTOR, PITCH_MODE, PITCH_SMOOTHING,
+    SMOOTHING_EMA_ALPHA, STOP_FLAG, STREAM_ID, STREAM_STALLED, THRESHOLD_RATIO, TRAY_ICON_MODE,
+    TUNER_MODE, TUNING_SHIFT_CENTS, WINDOW_FUNCTION,
+};
+use crate::dsp::{
+    get_string_targets, refresh_tray_icon, run_analysis_thread, HumFilterMode, NoiseReduction,
+    PeakInterpolator, PeakRefinement, PitchDetectorKind, PitchSmoothing, WindowKind,
+};
+use crate::instrument::{Instrument, InstrumentInfo};
+use crate::pitch::{
+    normalize_custom_pitch, validate_capo, validate_tuning_shift, PitchMode, PitchPreset,
+    PitchReference, StringTarget, PITCH_PRESETS,
+};
+use crate::temperament::{Temperament, TemperamentKind, MAX_EDO_DIVISIONS, MIN_EDO_DIVISIONS};
+use crate::tuning::{
+    builtin_offset_tables, builtin_tunings, find_offset_table, find_tuning,
+    is_builtin_offset_table, is_builtin_tuning, standard_tuning, OffsetTable, StringPitch, Tuning,
+    NOTE_NAMES,
 };
-use crate::dsp::{refresh_tray_icon, run_analysis_thread};
 
 /// Supported locales
 const SUPPORTED_LOCALES: [&str; 2] = ["en", "ja"];
@@ -43,28 +65,109 @@ pub fn get_channel_mode() -> u32 {
 #[command]
 #[allow(dead_code)]
 pub fn set_pitch_mode(mode: u32) {
-    PITCH_MODE.store(mode, Ordering::SeqCst);
-    println!("Pitch mode set to: {}", mode);
+    let mode = PitchMode::from_u32(mode);
+    PITCH_MODE.store(mode as u32, Ordering::SeqCst);
+    invalidate_string_targets();
+    println!("Pitch mode set to: {}", mode.name());
 }
 
-/// カスタム基準ピッチを設定 (438.0-445.0 Hz)
+/// カスタム基準ピッチを設定 (400.0-480.0 Hz、0.1Hz刻みに丸める)
 #[command]
 #[allow(dead_code)]
 pub fn set_custom_pitch(pitch: f32) -> Result<(), String> {
-    if !(438.0..=445.0).contains(&pitch) {
-        return Err("Pitch must be between 438 and 445 Hz".to_string());
-    }
+    let pitch = normalize_custom_pitch(pitch)?;
     *CUSTOM_PITCH.write().unwrap() = pitch;
+    invalidate_string_targets();
     println!("Custom pitch set to: {:.1} Hz", pitch);
     Ok(())
 }
 
-/// チューニングシフトを設定（半音数、負の値）
+/// チューニングシフトを設定（半音数、上下とも・小数も可、-12〜12）
 #[command]
 #[allow(dead_code)]
-pub fn set_tuning_shift(semitones: i32) {
-    TUNING_SHIFT.store(semitones, Ordering::SeqCst);
-    println!("Tuning shift set to: {} semitones", semitones);
+pub fn set_tuning_shift(semitones: f32) -> Result<(), String> {
+    set_tuning_shift_cents(semitones * 100.0)
+}
+
+/// チューニングシフトをセント単位で設定（-1200〜1200）
+#[command]
+pub fn set_tuning_shift_cents(cents: f32) -> Result<(), String> {
+    let cents = validate_tuning_shift(cents)?;
+    *TUNING_SHIFT_CENTS.write().map_err(|e| e.to_string())? = cents;
+    invalidate_string_targets();
+    println!("Tuning shift set to: {:+.1} cents", cents);
+    Ok(())
+}
+
+/// カポの位置を設定（0=なし、1〜12フレット）
+#[command]
+pub fn set_capo(fret: u32) -> Result<(), String> {
+    let fret = validate_capo(fret)?;
+    CAPO.store(fret, Ordering::SeqCst);
+    invalidate_string_targets();
+    println!("Capo set to fret: {}", fret);
+    Ok(())
+}
+
+/// 現在のカポの位置を取得
+#[command]
+pub fn get_capo() -> u32 {
+    CAPO.load(Ordering::SeqCst)
+}
+
+/// 基準ピッチのプリセット一覧を取得
+#[command]
+pub fn get_pitch_presets() -> Vec<PitchPreset> {
+    PITCH_PRESETS.to_vec()
+}
+
+/// プリセット（id）の基準ピッチをカスタムモードで設定して保存し、そのA4を返す
+/// 変更後の基準は pitch_reference_changed イベントでフロントエンドに通知する
+#[command]
+pub fn set_pitch_preset(app: tauri::AppHandle, id: String) -> Result<f32, String> {
+    let preset = PITCH_PRESETS
+        .iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| format!("Unknown pitch preset: {}", id))?;
+    set_custom_pitch(preset.a4)?;
+    set_pitch_mode(PitchMode::Custom as u32);
+
+    let mut settings = get_settings()?;
+    settings.pitch_mode = Some(PitchMode::Custom.name().to_string());
+    settings.custom_pitch = Some(preset.a4);
+    set_settings(settings)?;
+    let _ = app.emit("pitch_reference_changed", get_pitch_reference());
+    Ok(preset.a4)
+}
+
+/// 音名・目標・セント値の計算に使っている基準（計算済みのA4と移調量を付加）
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PitchReferenceInfo {
+    #[serde(flatten)]
+    pub reference: PitchReference,
+    /// 音名の基準のA4
+    pub note_a4: f32,
+    /// 目標周波数の基準のA4
+    pub target_a4: f32,
+    /// 目標の移調量（半音）
+    pub transposition: i32,
+    /// 現在の楽器・チューニングの各弦の目標（解析で使うものと同じ）
+    pub string_targets: Vec<StringTarget>,
+}
+
+/// 現在の基準ピッチ・シフト・カポ・弦ごとのオフセットと、各弦の目標を取得
+#[command]
+pub fn get_pitch_reference() -> PitchReferenceInfo {
+    let reference = PitchReference::current();
+    let instrument = Instrument::from_u32(INSTRUMENT.load(Ordering::SeqCst));
+    PitchReferenceInfo {
+        note_a4: reference.note_a4(),
+        target_a4: reference.target_a4(),
+        transposition: reference.transposition(),
+        string_targets: get_string_targets(instrument, &reference),
+        reference,
+    }
 }
 
 /// 6弦ドロップチューニングを設定
@@ -73,6 +176,7 @@ pub fn set_tuning_shift(semitones: i32) {
 pub fn set_drop_tuning(enabled: bool, note: u32) {
     DROP_TUNING_ENABLED.store(enabled, Ordering::SeqCst);
     DROP_TUNING_NOTE.store(note, Ordering::SeqCst);
+    invalidate_string_targets();
     println!(
         "Drop tuning set to: enabled={}, note={}",
         enabled,
@@ -86,6 +190,627 @@ pub fn set_drop_tuning(enabled: bool, note: u32) {
     );
 }
 
+/// チューナーモードを設定して保存 (0=guitar, 1=chromatic, 2=strum, 3=strobe)
+#[command]
+pub fn set_tuner_mode(mode: u32) -> Result<(), String> {
+    let tuner_mode = TunerMode::from_u32(mode);
+    TUNER_MODE.store(tuner_mode as u32, Ordering::SeqCst);
+    println!("Tuner mode set to: {}", tuner_mode.name());
+
+    let mut settings = get_settings()?;
+    settings.tuner_mode = Some(tuner_mode.name().to_string());
+    set_settings(settings)
+}
+
+/// 現在のチューナーモードを取得
+#[command]
+pub fn get_tuner_mode() -> u32 {
+    TUNER_MODE.load(Ordering::SeqCst)
+}
+
+/// 楽器を設定（アクティブなチューニングは楽器の標準チューニングに切り替え、両方を設定に保存する）
+#[command]
+pub fn set_instrument(instrument: u32) -> Result<(), String> {
+    let instrument = Instrument::from_u32(instrument);
+    let tuning = instrument.default_tuning();
+    let tuning_name = tuning.name.clone();
+    INSTRUMENT.store(instrument as u32, Ordering::SeqCst);
+    *ACTIVE_TUNING.write().unwrap() = tuning;
+    invalidate_string_targets();
+    println!("Instrument set to: {}", instrument.name());
+
+    let mut settings = get_settings()?;
+    settings.instrument = Some(instrument.name().to_string());
+    settings.tuning = Some(tuning_name);
+    set_settings(settings)
+}
+
+/// 現在の楽器を取得
+#[command]
+pub fn get_instrument() -> u32 {
+    INSTRUMENT.load(Ordering::SeqCst)
+}
+
+/// 利用可能な楽器の一覧を取得
+#[command]
+pub fn get_instruments() -> Vec<InstrumentInfo> {
+    Instrument::ALL.into_iter().map(Instrument::info).collect()
+}
+
+/// チューニング一覧の要素（組み込みプリセットかどうかを付加）
+#[derive(Debug, Serialize, Clone)]
+pub struct TuningEntry {
+    #[serde(flatten)]
+    pub tuning: Tuning,
+    pub builtin: bool,
+}
+
+/// 利用可能なチューニング一覧を取得（プリセット + ユーザー定義）
+#[command]
+pub fn get_tunings() -> Result<Vec<TuningEntry>, String> {
+    let user_tunings = get_settings()?.user_tunings.unwrap_or_default();
+    let presets = builtin_tunings().into_iter().map(|tuning| TuningEntry {
+        tuning,
+        builtin: true,
+    });
+    let user = user_tunings.into_iter().map(|tuning| TuningEntry {
+        tuning,
+        builtin: false,
+    });
+    Ok(presets.chain(user).collect())
+}
+
+/// アクティブなチューニングを名前で設定（設定に保存する）
+#[command]
+pub fn set_tuning(name: String) -> Result<(), String> {
+    let mut settings = get_settings()?;
+    let tuning = find_tuning(&name, settings.user_tunings.as_deref().unwrap_or_default())
+        .ok_or_else(|| format!("Unknown tuning: {}", name))?;
+    *ACTIVE_TUNING.write().unwrap() = tuning;
+    invalidate_string_targets();
+    println!("Tuning set to: {}", name);
+
+    settings.tuning = Some(name);
+    set_settings(settings)
+}
+
+/// 現在のチューニングを取得
+#[command]
+pub fn get_tuning() -> Tuning {
+    ACTIVE_TUNING.read().unwrap().clone()
+}
+
+/// ユーザー定義チューニングを作成して保存（同名のユーザー定義があれば上書き）
+#[command]
+pub fn create_tuning(name: String, strings: Vec<StringPitch>) -> Result<(), String> {
+    let tuning = Tuning {
+        name: name.trim().to_string(),
+        strings,
+    };
+    tuning.validate()?;
+    if is_builtin_tuning(&tuning.name) {
+        return Err(format!("Cannot overwrite built-in tuning: {}", tuning.name));
+    }
+
+    let mut settings = get_settings()?;
+    let user_tunings = settings.user_tunings.get_or_insert_with(Vec::new);
+    match user_tunings.iter_mut().find(|t| t.name == tuning.name) {
+        Some(existing) => *existing = tuning.clone(),
+        None => user_tunings.push(tuning.clone()),
+    }
+    set_settings(settings)?;
+
+    // 編集中のチューニングがアクティブなら即時反映
+    let mut active = ACTIVE_TUNING.write().unwrap();
+    if active.name == tuning.name {
+        *active = tuning.clone();
+        invalidate_string_targets();
+    }
+    println!("Tuning saved: {}", tuning.name);
+    Ok(())
+}
+
+/// ユーザー定義チューニングを削除して保存
+#[command]
+pub fn delete_tuning(name: String) -> Result<(), String> {
+    if is_builtin_tuning(&name) {
+        return Err(format!("Cannot delete built-in tuning: {}", name));
+    }
+
+    let mut settings = get_settings()?;
+    let user_tunings = settings.user_tunings.get_or_insert_with(Vec::new);
+    let len = user_tunings.len();
+    user_tunings.retain(|t| t.name != name);
+    if user_tunings.len() == len {
+        return Err(format!("Unknown tuning: {}", name));
+    }
+
+    // 削除したチューニングがアクティブなら標準チューニングに戻す
+    let mut active = ACTIVE_TUNING.write().unwrap();
+    if active.name == name {
+        *active = standard_tuning();
+        invalidate_string_targets();
+        settings.tuning = Some(active.name.clone());
+    }
+    set_settings(settings)?;
+    println!("Tuning deleted: {}", name);
+    Ok(())
+}
+
+/// オフセット表一覧の要素（組み込みかどうかを付加）
+#[derive(Debug, Serialize, Clone)]
+pub struct OffsetTableEntry {
+    #[serde(flatten)]
+    pub table: OffsetTable,
+    pub builtin: bool,
+}
+
+/// 利用可能なオフセット表の一覧を取得（組み込み + ユーザー定義）
+#[command]
+pub fn get_offset_tables() -> Result<Vec<OffsetTableEntry>, String> {
+    let user_tables = get_settings()?.user_offset_tables.unwrap_or_default();
+    let presets = builtin_offset_tables()
+        .into_iter()
+        .map(|table| OffsetTableEntry {
+            table,
+            builtin: true,
+        });
+    let user = user_tables.into_iter().map(|table| OffsetTableEntry {
+        table,
+        builtin: false,
+    });
+    Ok(presets.chain(user).collect())
+}
+
+/// 弦ごとのオフセット表を名前で選択して保存（Noneでオフセットなし）
+#[command]
+pub fn set_offset_table(name: Option<String>) -> Result<(), String> {
+    let mut settings = get_settings()?;
+    let table = match &name {
+        Some(name) => Some(
+            find_offset_table(
+                name,
+                settings.user_offset_tables.as_deref().unwrap_or_default(),
+            )
+            .ok_or_else(|| format!("Unknown offset table: {}", name))?,
+        ),
+        None => None,
+    };
+    settings.offset_table = name;
+    set_settings(settings)?;
+
+    println!(
+        "Offset table set to: {}",
+        table.as_ref().map_or("none", |t| t.name.as_str())
+    );
+    *ACTIVE_OFFSET_TABLE.write().unwrap() = table;
+    invalidate_string_targets();
+    Ok(())
+}
+
+/// 現在のオフセット表を取得
+#[command]
+pub fn get_offset_table() -> Option<OffsetTable> {
+    ACTIVE_OFFSET_TABLE.read().unwrap().clone()
+}
+
+/// ユーザー定義のオフセット表を作成して保存（同名のユーザー定義があれば上書き）
+#[command]
+pub fn create_offset_table(name: String, offsets: Vec<f32>) -> Result<(), String> {
+    let table = OffsetTable {
+        name: name.trim().to_string(),
+        offsets,
+    };
+    table.validate()?;
+    if is_builtin_offset_table(&table.name) {
+        return Err(format!(
+            "Cannot overwrite built-in offset table: {}",
+            table.name
+        ));
+    }
+
+    let mut settings = get_settings()?;
+    let user_tables = settings.user_offset_tables.get_or_insert_with(Vec::new);
+    match user_tables.iter_mut().find(|t| t.name == table.name) {
+        Some(existing) => *existing = table.clone(),
+        None => user_tables.push(table.clone()),
+    }
+    set_settings(settings)?;
+
+    // 編集中の表が選択中なら即時反映
+    let mut active = ACTIVE_OFFSET_TABLE.write().unwrap();
+    if active.as_ref().is_some_and(|t| t.name == table.name) {
+        *active = Some(table.clone());
+        invalidate_string_targets();
+    }
+    println!("Offset table saved: {}", table.name);
+    Ok(())
+}
+
+/// ユーザー定義のオフセット表を削除して保存
+#[command]
+pub fn delete_offset_table(name: String) -> Result<(), String> {
+    if is_builtin_offset_table(&name) {
+        return Err(format!("Cannot delete built-in offset table: {}", name));
+    }
+
+    let mut settings = get_settings()?;
+    let user_tables = settings.user_offset_tables.get_or_insert_with(Vec::new);
+    let len = user_tables.len();
+    user_tables.retain(|t| t.name != name);
+    if user_tables.len() == len {
+        return Err(format!("Unknown offset table: {}", name));
+    }
+
+    // 削除した表が選択中ならオフセットなしに戻す
+    let mut active = ACTIVE_OFFSET_TABLE.write().unwrap();
+    if active.as_ref().is_some_and(|t| t.name == name) {
+        *active = None;
+        invalidate_string_targets();
+        settings.offset_table = None;
+    }
+    set_settings(settings)?;
+    println!("Offset table deleted: {}", name);
+    Ok(())
+}
+
+/// 音律の情報
+#[derive(Debug, Serialize, Clone)]
+pub struct TemperamentInfo {
+    pub kind: &'static str,
+    pub name: String,
+    /// 主音（C=0〜B=11、12音の音律のみ）
+    pub key: u32,
+    /// 各音度の音度0からのセント
+    pub degrees: Vec<f32>,
+    /// 周期（セント）
+    pub period: f32,
+}
+
+impl From<&Temperament> for TemperamentInfo {
+    fn from(temperament: &Temperament) -> Self {
+        Self {
+            kind: temperament.kind.name(),
+            name: temperament.name.clone(),
+            key: temperament.key,
+            degrees: temperament.degrees.clone(),
+            period: temperament.period,
+        }
+    }
+}
+
+/// 音律を設定して保存 (0=平均律, 1=純正律, 2=ピタゴラス, 3=ミーントーン, 4=ヴェルクマイスターIII)
+/// divisions: 平均律の分割数（省略時12）、key: 主音（C=0〜B=11、省略時C）
+/// A4は常に基準ピッチの高さになり、主音とその他の音は音律に従って平均律からずれる
+#[command]
+pub fn set_temperament(kind: u32, divisions: Option<u32>, key: Option<u32>) -> Result<(), String> {
+    let (kind, divisions, key) = apply_temperament(kind, divisions, key)?;
+
+    let mut settings = get_settings()?;
+    settings.temperament = Some(kind.name().to_string());
+    settings.temperament_divisions = Some(divisions);
+    settings.temperament_key = Some(NOTE_NAMES[key as usize].to_string());
+    settings.scala_file = None;
+    settings.scala_keyboard_file = None;
+    set_settings(settings)
+}
+
+/// 音律を検証して反映（反映した種類・分割数・主音を返す）
+fn apply_temperament(
+    kind: u32,
+    divisions: Option<u32>,
+    key: Option<u32>,
+) -> Result<(TemperamentKind, u32, u32), String> {
+    let divisions = divisions.unwrap_or(12);
+    if !(MIN_EDO_DIVISIONS..=MAX_EDO_DIVISIONS).contains(&divisions) {
+        return Err(format!(
+            "Divisions must be between {} and {}",
+            MIN_EDO_DIVISIONS, MAX_EDO_DIVISIONS
+        ));
+    }
+    let key = key.unwrap_or(0);
+    if key >= 12 {
+        return Err(format!("Invalid key: {}", key));
+    }
+    let kind = TemperamentKind::from_u32(kind);
+    let temperament = Temperament::from_kind(kind, divisions, key)?;
+    println!("Temperament set to: {}", temperament.name);
+    *ACTIVE_TEMPERAMENT.write().unwrap() = temperament;
+    invalidate_string_targets();
+    Ok((kind, divisions, key))
+}
+
+/// Scalaファイル（.scl、任意で.kbm）を読み込んで音律に設定し、ファイルのパスを保存
+#[command]
+pub fn load_scala_file(
+    path: String,
+    keyboard_path: Option<String>,
+) -> Result<TemperamentInfo, String> {
+    let info = apply_scala_file(&path, keyboard_path.as_deref())?;
+
+    let mut settings = get_settings()?;
+    settings.temperament = Some(TemperamentKind::Scala.name().to_string());
+    settings.scala_file = Some(path);
+    settings.scala_keyboard_file = keyboard_path;
+    set_settings(settings)?;
+    Ok(info)
+}
+
+/// Scalaファイルを読み込んで反映
+fn apply_scala_file(path: &str, keyboard_path: Option<&str>) -> Result<TemperamentInfo, String> {
+    let temperament = Temperament::load_scala(path, keyboard_path)?;
+    println!("Temperament loaded from: {}", path);
+    let info = TemperamentInfo::from(&temperament);
+    *ACTIVE_TEMPERAMENT.write().unwrap() = temperament;
+    invalidate_string_targets();
+    Ok(info)
+}
+
+/// 現在の音律を取得
+#[command]
+pub fn get_temperament() -> TemperamentInfo {
+    TemperamentInfo::from(&*ACTIVE_TEMPERAMENT.read().unwrap())
+}
+
+/// ピッチ検出アルゴリズムを設定して保存 (0=FFT, 1=YIN, 2=McLeod)
+#[command]
+pub fn set_pitch_detector(mode: u32) -> Result<(), String> {
+    let kind = PitchDetectorKind::from_u32(mode);
+    PITCH_DETECTOR.store(kind as u32, Ordering::SeqCst);
+    println!("Pitch detector set to: {}", kind.name());
+
+    let mut settings = get_settings()?;
+    settings.pitch_detector = Some(kind.name().to_string());
+    set_settings(settings)
+}
+
+/// 現在のピッチ検出アルゴリズムを取得
+#[command]
+pub fn get_pitch_detector() -> u32 {
+    PITCH_DETECTOR.load(Ordering::SeqCst)
+}
+
+/// FFT解析の窓関数を設定して保存 (0=Blackman-Harris, 1=Hann, 2=Hamming, 3=Kaiser, 4=flat-top)
+/// beta: Kaiser窓のβ（0.0-20.0、省略時は現在の値）
+#[command]
+pub fn set_window_function(kind: u32, beta: Option<f32>) -> Result<(), String> {
+    if let Some(beta) = beta {
+        if !(0.0..=20.0).contains(&beta) {
+            return Err("Kaiser beta must be between 0 and 20".to_string());
+        }
+        *KAISER_BETA.write().map_err(|e| e.to_string())? = beta;
+    }
+    let window = WindowKind::from_u32(kind, *KAISER_BETA.read().map_err(|e| e.to_string())?);
+    WINDOW_FUNCTION.store(window.id(), Ordering::SeqCst);
+    println!("Window function set to: {}", window.name());
+
+    let mut settings = get_settings()?;
+    settings.window_function = Some(window.name().to_string());
+    settings.kaiser_beta = Some(*KAISER_BETA.read().map_err(|e| e.to_string())?);
+    set_settings(settings)
+}
+
+/// 現在の窓関数を取得
+#[command]
+pub fn get_window_function() -> u32 {
+    WINDOW_FUNCTION.load(Ordering::SeqCst)
+}
+
+/// 現在のKaiser窓のβを取得
+#[command]
+pub fn get_kaiser_beta() -> Result<f32, String> {
+    Ok(*KAISER_BETA.read().map_err(|e| e.to_string())?)
+}
+
+/// ピークbinの補間方法を設定して保存 (0=Gaussian, 1=parabolic, 2=Quinn2, 3=Jain)
+#[command]
+pub fn set_peak_interpolator(mode: u32) -> Result<(), String> {
+    let interpolator = PeakInterpolator::from_u32(mode);
+    PEAK_INTERPOLATOR.store(interpolator as u32, Ordering::SeqCst);
+    println!("Peak interpolator set to: {}", interpolator.name());
+
+    let mut settings = get_settings()?;
+    settings.peak_interpolator = Some(interpolator.name().to_string());
+    set_settings(settings)
+}
+
+/// 現在のピークbinの補間方法を取得
+#[command]
+pub fn get_peak_interpolator() -> u32 {
+    PEAK_INTERPOLATOR.load(Ordering::SeqCst)
+}
+
+/// ピーク周波数の精密化方法を設定して保存 (0=補間のみ, 1=ズームFFT)
+#[command]
+pub fn set_peak_refinement(mode: u32) -> Result<(), String> {
+    let refinement = PeakRefinement::from_u32(mode);
+    PEAK_REFINEMENT.store(refinement as u32, Ordering::SeqCst);
+    println!("Peak refinement set to: {}", refinement.name());
+
+    let mut settings = get_settings()?;
+    settings.peak_refinement = Some(refinement.name().to_string());
+    set_settings(settings)
+}
+
+/// 現在のピーク周波数の精密化方法を取得
+#[command]
+pub fn get_peak_refinement() -> u32 {
+    PEAK_REFINEMENT.load(Ordering::SeqCst)
+}
+
+/// 電源ハム除去の前処理を設定して保存 (0=off, 1=auto, 2=50Hz, 3=60Hz)
+#[command]
+pub fn set_hum_filter(mode: u32) -> Result<(), String> {
+    let mode = HumFilterMode::from_u32(mode);
+    HUM_FILTER.store(mode as u32, Ordering::SeqCst);
+    println!("Hum filter set to: {}", mode.name());
+
+    let mut settings = get_settings()?;
+    settings.hum_filter = Some(mode.name().to_string());
+    set_settings(settings)
+}
+
+/// 現在の電源ハム除去の前処理を取得
+#[command]
+pub fn get_hum_filter() -> u32 {
+    HUM_FILTER.load(Ordering::SeqCst)
+}
+
+/// ノッチを適用している電源周波数を取得（Hz、未判定・前処理なしならNone）
+#[command]
+pub fn get_mains_frequency() -> Option<u32> {
+    match MAINS_FREQUENCY.load(Ordering::SeqCst) {
+        0 => None,
+        hz => Some(hz),
+    }
+}
+
+/// 検出した周波数の平滑化方法を設定して保存 (0=中央値, 1=EMA, 2=カルマンフィルタ)
+/// ema_alpha: EMAの係数（0より大きく1以下）
+/// process_noise / measurement_noise: カルマンフィルタのノイズの分散（セント²、省略時は現在の値）
+#[command]
+pub fn set_pitch_smoothing(
+    mode: u32,
+    ema_alpha: Option<f32>,
+    process_noise: Option<f32>,
+    measurement_noise: Option<f32>,
+) -> Result<(), String> {
+    let smoothing = apply_pitch_smoothing(mode, ema_alpha, process_noise, measurement_noise)?;
+
+    let mut settings = get_settings()?;
+    settings.pitch_smoothing = Some(smoothing.name().to_string());
+    settings.smoothing_ema_alpha = Some(*SMOOTHING_EMA_ALPHA.read().map_err(|e| e.to_string())?);
+    settings.kalman_process_noise = Some(*KALMAN_PROCESS_NOISE.read().map_err(|e| e.to_string())?);
+    settings.kalman_measurement_noise =
+        Some(*KALMAN_MEASUREMENT_NOISE.read().map_err(|e| e.to_string())?);
+    set_settings(settings)
+}
+
+/// 平滑化方法とパラメータを検証して反映
+fn apply_pitch_smoothing(
+    mode: u32,
+    ema_alpha: Option<f32>,
+    process_noise: Option<f32>,
+    measurement_noise: Option<f32>,
+) -> Result<PitchSmoothing, String> {
+    if ema_alpha.is_some_and(|a| !(a > 0.0 && a <= 1.0)) {
+        return Err("EMA alpha must be greater than 0 and at most 1".to_string());
+    }
+    if process_noise.is_some_and(|q| !(q >= 0.0 && q.is_finite())) {
+        return Err("Process noise must be a non-negative number".to_string());
+    }
+    if measurement_noise.is_some_and(|r| !(r > 0.0 && r.is_finite())) {
+        return Err("Measurement noise must be a positive number".to_string());
+    }
+    if let Some(alpha) = ema_alpha {
+        *SMOOTHING_EMA_ALPHA.write().map_err(|e| e.to_string())? = alpha;
+    }
+    if let Some(q) = process_noise {
+        *KALMAN_PROCESS_NOISE.write().map_err(|e| e.to_string())? = q;
+    }
+    if let Some(r) = measurement_noise {
+        *KALMAN_MEASUREMENT_NOISE
+            .write()
+            .map_err(|e| e.to_string())? = r;
+    }
+    let smoothing = PitchSmoothing::from_u32(mode);
+    PITCH_SMOOTHING.store(smoothing as u32, Ordering::SeqCst);
+    println!("Pitch smoothing set to: {}", smoothing.name());
+    Ok(smoothing)
+}
+
+/// 現在の平滑化方法を取得
+#[command]
+pub fn get_pitch_smoothing() -> u32 {
+    PITCH_SMOOTHING.load(Ordering::SeqCst)
+}
+
+/// 平滑化のパラメータ
+#[derive(Debug, Serialize, Clone)]
+pub struct SmoothingParameters {
+    pub ema_alpha: f32,
+    pub process_noise: f32,
+    pub measurement_noise: f32,
+}
+
+/// 現在の平滑化のパラメータを取得
+#[command]
+pub fn get_smoothing_parameters() -> Result<SmoothingParameters, String> {
+    Ok(SmoothingParameters {
+        ema_alpha: *SMOOTHING_EMA_ALPHA.read().map_err(|e| e.to_string())?,
+        process_noise: *KALMAN_PROCESS_NOISE.read().map_err(|e| e.to_string())?,
+        measurement_noise: *KALMAN_MEASUREMENT_NOISE.read().map_err(|e| e.to_string())?,
+    })
+}
+
+/// 撥弦の直後に解析しない時間を設定して保存（ミリ秒、0〜500）
+#[command]
+pub fn set_onset_skip(ms: u32) -> Result<(), String> {
+    apply_onset_skip(ms)?;
+
+    let mut settings = get_settings()?;
+    settings.onset_skip_ms = Some(ms);
+    set_settings(settings)
+}
+
+/// 撥弦の直後に解析しない時間を検証して反映
+fn apply_onset_skip(ms: u32) -> Result<(), String> {
+    if ms > MAX_ONSET_SKIP_MS {
+        return Err(format!(
+            "Onset skip must be between 0 and {} ms",
+            MAX_ONSET_SKIP_MS
+        ));
+    }
+    ONSET_SKIP_MS.store(ms, Ordering::SeqCst);
+    println!("Onset skip set to: {}ms", ms);
+    Ok(())
+}
+
+/// 現在の撥弦の直後に解析しない時間を取得（ミリ秒）
+#[command]
+pub fn get_onset_skip() -> u32 {
+    ONSET_SKIP_MS.load(Ordering::SeqCst)
+}
+
+/// 解析のホップサイズを設定して保存（フレーム数、256〜FFT_SIZE）
+#[command]
+pub fn set_hop_size(hop_size: u32) -> Result<(), String> {
+    apply_hop_size(hop_size)?;
+
+    let mut settings = get_settings()?;
+    settings.hop_size = Some(hop_size);
+    set_settings(settings)
+}
+
+/// ホップサイズを検証して反映
+fn apply_hop_size(hop_size: u32) -> Result<(), String> {
+    if !(MIN_HOP_SIZE..=FFT_SIZE).contains(&(hop_size as usize)) {
+        return Err(format!(
+            "Hop size must be between {} and {} frames",
+            MIN_HOP_SIZE, FFT_SIZE
+        ));
+    }
+    HOP_SIZE.store(hop_size, Ordering::SeqCst);
+    println!("Hop size set to: {}", hop_size);
+    Ok(())
+}
+
+/// 現在のホップサイズを取得
+#[command]
+pub fn get_hop_size() -> u32 {
+    HOP_SIZE.load(Ordering::SeqCst)
+}
+
+/// 入力ストリームの状態を取得 ("active" | "stalled")
+#[command]
+pub fn get_stream_state() -> String {
+    if STREAM_STALLED.load(Ordering::SeqCst) {
+        "stalled".to_string()
+    } else {
+        "active".to_string()
+    }
+}
+
 /// Set whether the window should always be on top
 #[command]
 pub fn set_always_on_top(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
@@ -162,11 +887,19 @@ pub fn get_tray_icon_mode() -> u32 {
     TRAY_ICON_MODE.load(Ordering::SeqCst)
 }
 
+/// 最後の検出結果（信頼度・検出周波数・弦・サンプルレート・FFT長などの診断情報）を取得
+/// まだ何も検出していない、または無音が続いてリセットされた後はNone
+#[command]
+pub fn get_last_detection() -> Result<Option<TuningInfo>, String> {
+    let info = LAST_TUNING_INFO.lock().map_err(|e| e.to_string())?;
+    Ok((!info.note_name.is_empty()).then(|| info.clone()))
+}
+
 /// Set threshold ratio (range: 1.1 to 10.0)
 #[command]
 pub fn set_threshold(ratio: f32) -> Result<(), String> {
     let mut threshold = THRESHOLD_RATIO.lock().map_err(|e| e.to_string())?;
-    *threshold = ratio.max(1.1).min(10.0);
+    *threshold = ratio.clamp(MIN_THRESHOLD_RATIO, MAX_THRESHOLD_RATIO);
     println!("Threshold set to: {:.2}", *threshold);
     Ok(())
 }
@@ -178,6 +911,52 @@ pub fn get_threshold() -> Result<f32, String> {
     Ok(*threshold)
 }
 
+/// 無音を測定してノイズプロファイルを作り、ゲートと閾値を自動設定する
+/// 結果は測定が終わると noise_calibrated イベントで通知される
+/// ゲートと閾値は設定として保存されるが、ノイズプロファイルはこのセッションの間だけ有効
+#[command]
+pub fn calibrate_noise(duration_secs: Option<f32>) -> Result<(), String> {
+    let duration = duration_secs.unwrap_or(NOISE_CALIBRATION_DEFAULT_SECS);
+    if !(duration > 0.0 && duration <= NOISE_CALIBRATION_MAX_SECS) {
+        return Err(format!(
+            "Calibration duration must be between 0 and {} seconds",
+            NOISE_CALIBRATION_MAX_SECS
+        ));
+    }
+    NOISE_CALIBRATION_MS.store(
+        (duration * 1000.0).round().max(1.0) as u32,
+        Ordering::SeqCst,
+    );
+    println!("Noise calibration requested: {:.1}s", duration);
+    Ok(())
+}
+
+/// 現在の信号レベルのゲート（RMS）を取得
+#[command]
+pub fn get_noise_gate() -> Result<f32, String> {
+    Ok(*NOISE_GATE.read().map_err(|e| e.to_string())?)
+}
+
+/// スペクトル上のノイズ除去を設定 (0=off, 1=スペクトル減算, 2=Wiener)
+/// calibrate_noise で測定したノイズプロファイルを使うので、測定するまでは効果がない
+/// 設定は保存されるが、プロファイルは保存されないため、起動後は測定し直すまで効果がない
+#[command]
+pub fn set_noise_reduction(mode: u32) -> Result<(), String> {
+    let reduction = NoiseReduction::from_u32(mode);
+    NOISE_REDUCTION.store(reduction as u32, Ordering::SeqCst);
+    println!("Noise reduction set to: {}", reduction.name());
+
+    let mut settings = get_settings()?;
+    settings.noise_reduction = Some(reduction.name().to_string());
+    set_settings(settings)
+}
+
+/// 現在のスペクトル上のノイズ除去を取得
+#[command]
+pub fn get_noise_reduction() -> u32 {
+    NOISE_REDUCTION.load(Ordering::SeqCst)
+}
+
 /// Get audio device list
 #[command]
 pub fn get_audio_devices() -> Result<Vec<String>, String> {
@@ -200,10 +979,10 @@ pub fn start_listening(app: tauri::AppHandle, device_name: String) -> Result<(),
     let device = find_device_by_name(&device_name)?;
 
     // Start audio stream
-    let (buffer, sample_rate, channels) = start_audio_stream(&device)?;
+    let (input, sample_rate, channels) = start_audio_stream(&device)?;
 
     // Launch analysis thread
-    run_analysis_thread(app, buffer, sample_rate, channels);
+    run_analysis_thread(app, input, sample_rate, channels);
 
     Ok(())
 }
@@ -212,17 +991,43 @@ pub fn start_listening(app: tauri::AppHandle, device_name: String) -> Result<(),
 pub struct Settings {
     pub device_name: Option<String>,
     pub threshold: Option<f32>,
+    pub noise_gate: Option<f32>,
+    pub noise_reduction: Option<String>, // "off" | "spectral_subtraction" | "wiener"
     pub channel_mode: Option<u32>,
     pub tray_icon_mode: Option<u32>,
     // Settings for pitch reference and tuning
     pub pitch_mode: Option<String>, // "standard" | "custom" | "shift"
-    pub custom_pitch: Option<f32>,  // 438.0-445.0
-    pub tuning_shift: Option<i32>,  // -1 to -5
+    pub custom_pitch: Option<f32>,  // 400.0-480.0
+    pub tuning_shift: Option<f32>,  // -12.0 to 12.0 semitones (fractional allowed)
+    pub capo: Option<u32>,          // 0-12 (fret, 0 = no capo)
     pub drop_tuning_enabled: Option<bool>, // Drop tuning enabled/disabled
     pub drop_tuning_note: Option<String>, // "D" | "C#" | "C" | "B"
     pub theme_mode: Option<String>, // "system" | "light" | "dark"
     pub always_on_top: Option<bool>, // Always display window on top
     pub locale: Option<String>,     // "en" | "ja"
+    pub pitch_detector: Option<String>, // "fft" | "yin" | "mcleod"
+    pub window_function: Option<String>, // "blackman_harris" | "hann" | "hamming" | "kaiser" | "flat_top"
+    pub kaiser_beta: Option<f32>,        // 0.0-20.0
+    pub peak_interpolator: Option<String>, // "gaussian" | "parabolic" | "quinn2" | "jain"
+    pub peak_refinement: Option<String>, // "interpolation" | "zoom"
+    pub hum_filter: Option<String>,      // "off" | "auto" | "50hz" | "60hz"
+    pub onset_skip_ms: Option<u32>,      // 0-500
+    pub pitch_smoothing: Option<String>, // "median" | "ema" | "kalman"
+    pub smoothing_ema_alpha: Option<f32>,
+    pub kalman_process_noise: Option<f32>,     // >= 0 (cents²)
+    pub kalman_measurement_noise: Option<f32>, // > 0 (cents²)
+    pub hop_size: Option<u32>,                 // Analysis hop size in frames (256-16384)
+    pub tuner_mode: Option<String>,            // "guitar" | "chromatic" | "strum" | "strobe"
+    pub instrument: Option<String>, // "guitar" | "guitar7" | "guitar8" | "baritone" | "ukulele" | "mandolin" | "violin" | "bass" | "bass5" | "bass6"
+    pub tuning: Option<String>,     // Active tuning name
+    pub user_tunings: Option<Vec<Tuning>>, // User-defined tunings
+    pub offset_table: Option<String>, // Active per-string offset table name
+    pub user_offset_tables: Option<Vec<OffsetTable>>, // User-defined offset tables
+    pub temperament: Option<String>, // "equal" | "just" | "pythagorean" | "meantone" | "werckmeister" | "scala"
+    pub temperament_divisions: Option<u32>, // 5-72 (equal temperament only)
+    pub temperament_key: Option<String>, // "C" .. "B"
+    pub scala_file: Option<String>,  // Path to the .scl file
+    pub scala_keyboard_file: Option<String>, // Path to the optional .kbm file
 }
 
 fn settings_path() -> PathBuf {
@@ -243,22 +1048,189 @@ pub fn get_settings() -> Result<Settings, String> {
         return Ok(Settings {
             device_name: None,
             threshold: None,
+            noise_gate: None,
+            noise_reduction: None,
             channel_mode: None,
             tray_icon_mode: None,
             pitch_mode: None,
             custom_pitch: None,
             tuning_shift: None,
+            capo: None,
             drop_tuning_enabled: None,
             drop_tuning_note: None,
             theme_mode: None,
             always_on_top: None,
             locale: None,
+            pitch_detector: None,
+            window_function: None,
+            kaiser_beta: None,
+            peak_interpolator: None,
+            peak_refinement: None,
+            hum_filter: None,
+            onset_skip_ms: None,
+            pitch_smoothing: None,
+            smoothing_ema_alpha: None,
+            kalman_process_noise: None,
+            kalman_measurement_noise: None,
+            hop_size: None,
+            tuner_mode: None,
+            instrument: None,
+            tuning: None,
+            user_tunings: None,
+            offset_table: None,
+            user_offset_tables: None,
+            temperament: None,
+            temperament_divisions: None,
+            temperament_key: None,
+            scala_file: None,
+            scala_keyboard_file: None,
         });
     }
     let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
     serde_json::from_str(&content).map_err(|e| e.to_string())
 }
 
+/// 保存済みの設定のうち、バックエンドのみで扱う解析設定を反映
+pub fn apply_analysis_settings(settings: &Settings) {
+    if let Some(mode) = settings
+        .pitch_mode
+        .as_deref()
+        .and_then(PitchMode::from_name)
+    {
+        PITCH_MODE.store(mode as u32, Ordering::SeqCst);
+    }
+    if let Some(pitch) = settings.custom_pitch {
+        let _ = set_custom_pitch(pitch);
+    }
+    if let Some(semitones) = settings.tuning_shift {
+        let _ = set_tuning_shift(semitones);
+    }
+    if let Some(fret) = settings.capo {
+        let _ = set_capo(fret);
+    }
+    if let Some(gate) = settings.noise_gate.filter(|g| g.is_finite() && *g > 0.0) {
+        *NOISE_GATE.write().unwrap() = gate;
+    }
+    if let Some(reduction) = settings
+        .noise_reduction
+        .as_deref()
+        .and_then(NoiseReduction::from_name)
+    {
+        NOISE_REDUCTION.store(reduction as u32, Ordering::SeqCst);
+    }
+    if let Some(kind) = settings
+        .pitch_detector
+        .as_deref()
+        .and_then(PitchDetectorKind::from_name)
+    {
+        PITCH_DETECTOR.store(kind as u32, Ordering::SeqCst);
+    }
+    if let Some(beta) = settings.kaiser_beta.filter(|b| (0.0..=20.0).contains(b)) {
+        *KAISER_BETA.write().unwrap() = beta;
+    }
+    if let Some(window) = settings
+        .window_function
+        .as_deref()
+        .and_then(|name| WindowKind::from_name(name, *KAISER_BETA.read().unwrap()))
+    {
+        WINDOW_FUNCTION.store(window.id(), Ordering::SeqCst);
+    }
+    if let Some(interpolator) = settings
+        .peak_interpolator
+        .as_deref()
+        .and_then(PeakInterpolator::from_name)
+    {
+        PEAK_INTERPOLATOR.store(interpolator as u32, Ordering::SeqCst);
+    }
+    if let Some(refinement) = settings
+        .peak_refinement
+        .as_deref()
+        .and_then(PeakRefinement::from_name)
+    {
+        PEAK_REFINEMENT.store(refinement as u32, Ordering::SeqCst);
+    }
+    if let Some(mode) = settings
+        .hum_filter
+        .as_deref()
+        .and_then(HumFilterMode::from_name)
+    {
+        HUM_FILTER.store(mode as u32, Ordering::SeqCst);
+    }
+    if let Some(ms) = settings.onset_skip_ms {
+        let _ = apply_onset_skip(ms);
+    }
+    if let Some(smoothing) = settings
+        .pitch_smoothing
+        .as_deref()
+        .and_then(PitchSmoothing::from_name)
+    {
+        let _ = apply_pitch_smoothing(
+            smoothing as u32,
+            settings.smoothing_ema_alpha,
+            settings.kalman_process_noise,
+            settings.kalman_measurement_noise,
+        );
+    }
+    if let Some(hop_size) = settings.hop_size {
+        let _ = apply_hop_size(hop_size);
+    }
+    if let Some(mode) = settings
+        .tuner_mode
+        .as_deref()
+        .and_then(TunerMode::from_name)
+    {
+        TUNER_MODE.store(mode as u32, Ordering::SeqCst);
+    }
+    // 楽器を先に反映し、保存済みのチューニングがあれば上書きする
+    if let Some(instrument) = settings
+        .instrument
+        .as_deref()
+        .and_then(Instrument::from_name)
+    {
+        INSTRUMENT.store(instrument as u32, Ordering::SeqCst);
+        *ACTIVE_TUNING.write().unwrap() = instrument.default_tuning();
+    }
+    if let Some(tuning) = settings
+        .tuning
+        .as_deref()
+        .and_then(|name| find_tuning(name, settings.user_tunings.as_deref().unwrap_or_default()))
+    {
+        *ACTIVE_TUNING.write().unwrap() = tuning;
+    }
+    if let Some(table) = settings.offset_table.as_deref().and_then(|name| {
+        find_offset_table(
+            name,
+            settings.user_offset_tables.as_deref().unwrap_or_default(),
+        )
+    }) {
+        *ACTIVE_OFFSET_TABLE.write().unwrap() = Some(table);
+    }
+    match settings
+        .temperament
+        .as_deref()
+        .and_then(TemperamentKind::from_name)
+    {
+        Some(TemperamentKind::Scala) => {
+            if let Some(path) = settings.scala_file.as_deref() {
+                let _ = apply_scala_file(path, settings.scala_keyboard_file.as_deref());
+            }
+        }
+        Some(kind) => {
+            let key = settings
+                .temperament_key
+                .as_deref()
+                .and_then(|name| NOTE_NAMES.iter().position(|n| *n == name));
+            let _ = apply_temperament(
+                kind as u32,
+                settings.temperament_divisions,
+                key.map(|k| k as u32),
+            );
+        }
+        None => {}
+    }
+    invalidate_string_targets();
+}
+
 #[command]
 pub fn set_settings(settings: Settings) -> Result<(), String> {
     let path = settings_path();
@@ -1,15 +1,33 @@
 use cpal::Stream;
 use once_cell::sync::Lazy;
-use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::{Mutex, RwLock};
 use std::time::Instant;
 
+use crate::dsp::NoiseProfile;
+use crate::temperament::Temperament;
+use crate::tuning::{standard_tuning, OffsetTable, Tuning};
+
 /// グローバルストリームの保持（dropされないようにする）
 pub static STREAM: Lazy<Mutex<Option<Stream>>> = Lazy::new(|| Mutex::new(None));
 
 /// 閾値設定（グローバル）
 pub static THRESHOLD_RATIO: Lazy<Mutex<f32>> = Lazy::new(|| Mutex::new(2.0));
 
+/// 信号レベルのゲート（RMS、これ未満は解析しない）- キャリブレーションで自動設定
+pub static NOISE_GATE: Lazy<RwLock<f32>> = Lazy::new(|| RwLock::new(RMS_THRESHOLD));
+
+/// 周波数ごとのノイズプロファイル（キャリブレーション前はNone）
+/// 設定ファイルには保存しない（ゲートと閾値だけが保存され、プロファイルは起動ごとに測定し直す）
+pub static NOISE_PROFILE: Lazy<RwLock<Option<NoiseProfile>>> = Lazy::new(|| RwLock::new(None));
+
+/// スペクトル上のノイズ除去 (0=off, 1=スペクトル減算, 2=Wiener)
+pub static NOISE_REDUCTION: AtomicU32 = AtomicU32::new(0);
+
+/// 要求されたノイズキャリブレーションの測定時間（ミリ秒、0=要求なし）
+pub static NOISE_CALIBRATION_MS: AtomicU32 = AtomicU32::new(0);
+
 /// チャンネル選択（0=左, 1=右, 2=両方の平均）
 pub static CHANNEL_MODE: AtomicU32 = AtomicU32::new(1); // デフォルトは右チャンネル
 
@@ -25,8 +43,30 @@ pub static PITCH_MODE: AtomicU32 = AtomicU32::new(0);
 /// カスタム基準ピッチ (Hz) - デフォルト440.0
 pub static CUSTOM_PITCH: Lazy<RwLock<f32>> = Lazy::new(|| RwLock::new(440.0));
 
-/// チューニングシフト（半音数、負の値）
-pub static TUNING_SHIFT: AtomicI32 = AtomicI32::new(0);
+/// チューニングシフト（A4=440Hzからのセント、上下とも・半音未満も可）
+pub static TUNING_SHIFT_CENTS: Lazy<RwLock<f32>> = Lazy::new(|| RwLock::new(0.0));
+
+/// アクティブなチューニング（各弦の目標音）
+pub static ACTIVE_TUNING: Lazy<RwLock<Tuning>> = Lazy::new(|| RwLock::new(standard_tuning()));
+
+/// 音名とセント値の基準にする音律（デフォルトは12平均律）
+pub static ACTIVE_TEMPERAMENT: Lazy<RwLock<Temperament>> =
+    Lazy::new(|| RwLock::new(Temperament::default()));
+
+/// カポの位置（フレット、0=なし）
+pub static CAPO: AtomicU32 = AtomicU32::new(0);
+
+/// 弦ごとのセントオフセット表（Noneならオフセットなし）
+pub static ACTIVE_OFFSET_TABLE: Lazy<RwLock<Option<OffsetTable>>> = Lazy::new(|| RwLock::new(None));
+
+/// 弦の目標に影響する設定（楽器・チューニング・基準ピッチ・カポ・オフセット・音律・ドロップ）の世代
+/// 解析スレッドは世代が変わったときだけ目標を作り直す
+pub static STRING_TARGETS_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// 弦の目標に影響する設定を変更したことを解析スレッドに知らせる（設定を書き込んだ後に呼ぶ）
+pub fn invalidate_string_targets() {
+    STRING_TARGETS_GENERATION.fetch_add(1, Ordering::SeqCst);
+}
 
 /// 6弦ドロップチューニング有効フラグ
 pub static DROP_TUNING_ENABLED: AtomicBool = AtomicBool::new(false);
@@ -34,6 +74,55 @@ pub static DROP_TUNING_ENABLED: AtomicBool = AtomicBool::new(false);
 /// 6弦ドロップ音名 (0=D, 1=C#, 2=C, 3=B)
 pub static DROP_TUNING_NOTE: AtomicU32 = AtomicU32::new(0);
 
+/// チューナーモード (0=guitar, 1=chromatic, 2=strum, 3=strobe)
+pub static TUNER_MODE: AtomicU32 = AtomicU32::new(0);
+
+/// 楽器 (0=guitar, 1=guitar7, 2=guitar8, 3=baritone, 4=ukulele, 5=mandolin, 6=violin,
+/// 7=bass, 8=bass5, 9=bass6)
+pub static INSTRUMENT: AtomicU32 = AtomicU32::new(0);
+
+/// ピッチ検出アルゴリズム (0=FFT, 1=YIN, 2=McLeod)
+pub static PITCH_DETECTOR: AtomicU32 = AtomicU32::new(0);
+
+/// FFT解析の窓関数 (0=Blackman-Harris, 1=Hann, 2=Hamming, 3=Kaiser, 4=flat-top)
+pub static WINDOW_FUNCTION: AtomicU32 = AtomicU32::new(0);
+
+/// Kaiser窓のβ - デフォルト8.6
+pub static KAISER_BETA: Lazy<RwLock<f32>> = Lazy::new(|| RwLock::new(8.6));
+
+/// ピークbinの補間方法 (0=Gaussian, 1=parabolic, 2=Quinn2, 3=Jain)
+pub static PEAK_INTERPOLATOR: AtomicU32 = AtomicU32::new(0);
+
+/// ピーク周波数の精密化方法 (0=補間のみ, 1=ズームFFT)
+pub static PEAK_REFINEMENT: AtomicU32 = AtomicU32::new(0);
+
+/// 電源ハム除去の前処理 (0=off, 1=auto, 2=50Hz, 3=60Hz)
+pub static HUM_FILTER: AtomicU32 = AtomicU32::new(0);
+
+/// ノッチを適用している電源周波数（Hz、0=未判定・前処理なし）
+pub static MAINS_FREQUENCY: AtomicU32 = AtomicU32::new(0);
+
+/// 検出した周波数の平滑化方法 (0=中央値, 1=EMA, 2=カルマンフィルタ)
+pub static PITCH_SMOOTHING: AtomicU32 = AtomicU32::new(0);
+
+/// EMAの係数 - デフォルト0.4
+pub static SMOOTHING_EMA_ALPHA: Lazy<RwLock<f32>> = Lazy::new(|| RwLock::new(0.4));
+
+/// カルマンフィルタのプロセスノイズ（セント²/解析）- デフォルト1.0
+pub static KALMAN_PROCESS_NOISE: Lazy<RwLock<f32>> = Lazy::new(|| RwLock::new(1.0));
+
+/// カルマンフィルタの観測ノイズ（セント²）- デフォルト25.0（標準偏差5セント）
+pub static KALMAN_MEASUREMENT_NOISE: Lazy<RwLock<f32>> = Lazy::new(|| RwLock::new(25.0));
+
+/// 撥弦の直後に解析しない時間（ミリ秒、アタックの間は音程が高めに揺れるため）
+pub static ONSET_SKIP_MS: AtomicU32 = AtomicU32::new(DEFAULT_ONSET_SKIP_MS);
+
+/// 解析のホップサイズ（新しく届いたフレーム数がこの値に達するたびに1回解析する）
+pub static HOP_SIZE: AtomicU32 = AtomicU32::new(DEFAULT_HOP_SIZE as u32);
+
+/// 入力ストリームが停止しているか（一定時間新しいサンプルが届いていない）
+pub static STREAM_STALLED: AtomicBool = AtomicBool::new(false);
+
 /// 解析スレッド制御用
 pub static STREAM_ID: AtomicU32 = AtomicU32::new(0);
 pub static STOP_FLAG: AtomicBool = AtomicBool::new(false);
@@ -46,12 +135,32 @@ pub static LAST_TUNING_INFO: Lazy<Mutex<TuningInfo>> =
 pub static TRAY_ICON_STATE: Lazy<Mutex<TrayIconState>> =
     Lazy::new(|| Mutex::new(TrayIconState::new()));
 
-/// チューニング情報構造体
-#[derive(Clone, Default)]
+/// チューニング情報構造体（最後の検出結果の診断情報を含む）
+#[derive(Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct TuningInfo {
     pub note_name: String,
+    /// 平滑化後の周波数
     pub frequency: f32,
     pub cents: f32,
+    pub target_freq: f32,
+    /// 平滑化前の検出周波数
+    pub raw_frequency: f32,
+    /// ピークとノイズレベルの比と倍音の一致度から求めた信頼度（0.0〜1.0）
+    pub confidence: f32,
+    /// ピークとノイズレベルの比（スペクトルを使わない検出器ではNone）
+    pub peak_to_noise: Option<f32>,
+    /// 目標とした弦のインデックス（チューニングの弦の順、クロマチックモードではNone）
+    pub string_index: Option<usize>,
+    pub sample_rate: usize,
+    /// 解析に使ったFFT長（スペクトルを使わない検出器ではNone）
+    pub fft_size: Option<usize>,
+    /// 解析に使った窓長（サンプル数）
+    pub window_size: usize,
+    /// 検出時のチューナーモード
+    pub mode: &'static str,
+    /// 解析したフレームの終端（ストリーム開始からの累計フレーム数）
+    pub sample_position: u64,
 }
 
 /// トレイアイコンの状態（ちらつき防止のためのキャッシュ）
@@ -184,22 +293,135 @@ pub fn determine_color_with_hysteresis(
 /// FFTサイズ（高精度のため16384に増加、分解能: 約2.9Hz @48kHz）
 pub const FFT_SIZE: usize = 16384;
 
-/// ギター弦の標準チューニング周波数（Hz）
-pub const GUITAR_FREQUENCIES: [f32; 6] = [
-    82.41,  // E2
-    110.0,  // A2
-    146.83, // D3
-    196.0,  // G3
-    246.94, // B3
-    329.63, // E4
-];
+/// 高音用の短い解析窓（約85ms @48kHz、低音弦にだけ長い窓を使って遅延を減らす）
+pub const SHORT_FFT_SIZE: usize = 4096;
+
+/// 短い窓の推定結果を採用する下限周波数（Hz、これより低い音は長い窓で解析し直す）
+pub const SHORT_WINDOW_MIN_FREQUENCY: f32 = 150.0;
+
+/// ホップサイズ（フレーム数、デフォルト2048で約43ms @48kHz）
+pub const DEFAULT_HOP_SIZE: usize = 2048;
+pub const MIN_HOP_SIZE: usize = 256;
+
+/// ストロボモードのホップサイズ上限（位相の回転を滑らかに送るため）
+pub const STROBE_MAX_HOP_SIZE: usize = 512;
+
+/// 新しいサンプルがこの時間届かなければストリーム停止とみなす（ミリ秒）
+pub const STREAM_STALL_TIMEOUT_MS: u64 = 500;
 
-/// ギター音判定の許容誤差（±15%）
-pub const GUITAR_TOLERANCE: f32 = 0.15;
+/// クロマチックモードの周波数解析範囲（Hz、A0〜C8+α）
+pub const CHROMATIC_MIN_FREQUENCY: f32 = 27.0;
+pub const CHROMATIC_MAX_FREQUENCY: f32 = 4200.0;
 
-/// 周波数解析範囲（Hz）
-pub const MIN_FREQUENCY: f32 = 75.0;
-pub const MAX_FREQUENCY: f32 = 350.0;
+/// チューナーモード
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TunerMode {
+    Guitar = 0,    // 楽器の弦の目標周波数に合わせる
+    Chromatic = 1, // 最も近い平均律の音に合わせる
+    Strum = 2,     // 開放弦のストロークから全弦を同時に測る
+    Strobe = 3,    // 弦の目標周波数に対する位相の回転速度を高頻度で送る
+}
+
+impl TunerMode {
+    /// 数値から変換（範囲外はギター）
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => Self::Chromatic,
+            2 => Self::Strum,
+            3 => Self::Strobe,
+            _ => Self::Guitar,
+        }
+    }
+
+    /// 設定ファイル用の名前から変換
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "guitar" => Some(Self::Guitar),
+            "chromatic" => Some(Self::Chromatic),
+            "strum" => Some(Self::Strum),
+            "strobe" => Some(Self::Strobe),
+            _ => None,
+        }
+    }
+
+    /// 設定ファイル・イベント用の名前
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Guitar => "guitar",
+            Self::Chromatic => "chromatic",
+            Self::Strum => "strum",
+            Self::Strobe => "strobe",
+        }
+    }
+}
+
+/// 基音推定（倍音積スペクトル）で評価する倍音数
+pub const HPS_HARMONICS: usize = 5;
 
-/// RMS閾値（ノイズフロア以下はスキップ）
+/// 1オクターブ下の候補を採用するスコア差の上限（倍音あたりの対数振幅差）
+pub const HPS_OCTAVE_TOLERANCE: f32 = 0.2;
+
+/// 倍音が存在するとみなすノイズフロアに対する比率（信頼度の算出用）
+pub const HPS_PRESENCE_RATIO: f32 = 3.0;
+
+/// 信頼度を最大とするピークとノイズレベルの比（dB、これ未満は比に応じて信頼度を下げる）
+pub const CONFIDENCE_FULL_SNR_DB: f32 = 40.0;
+
+/// RMS閾値（ノイズフロア以下はスキップ、キャリブレーション前の初期値）
 pub const RMS_THRESHOLD: f32 = 0.001;
+
+/// ピークとノイズフロアの比率の閾値の範囲
+pub const MIN_THRESHOLD_RATIO: f32 = 1.1;
+pub const MAX_THRESHOLD_RATIO: f32 = 10.0;
+
+/// ノイズキャリブレーションの測定時間（秒）
+pub const NOISE_CALIBRATION_DEFAULT_SECS: f32 = 3.0;
+pub const NOISE_CALIBRATION_MAX_SECS: f32 = 30.0;
+
+/// キャリブレーションで求めたノイズのRMSに対するゲートの倍率（+6dB）
+pub const NOISE_GATE_MARGIN: f32 = 2.0;
+
+/// 無音中に観測したノイズの最大値に対する比率の閾値の余裕
+pub const NOISE_RATIO_MARGIN: f32 = 1.25;
+
+/// 音が検出されていない間のノイズプロファイルの追従の時定数（秒）
+pub const NOISE_ADAPT_TIME_SECS: f32 = 10.0;
+
+/// スペクトル減算でノイズレベルに掛ける倍率（ノイズの揺らぎ分を多めに差し引く）
+pub const NOISE_SUBTRACTION_FACTOR: f32 = 1.5;
+
+/// ノイズ除去後に残す元の振幅の比率（ミュージカルノイズを抑える下限）
+pub const SPECTRAL_FLOOR: f32 = 0.05;
+
+/// 中央値で平滑化する検出値の数
+pub const MEDIAN_SMOOTHING_LENGTH: usize = 5;
+
+/// 直前の検出値からこれ以上離れたら別の音とみなして平滑化をリセットする（セント）
+pub const SMOOTHING_RESET_CENTS: f32 = 50.0;
+
+/// 撥弦の直後に解析しない時間（ミリ秒）
+pub const DEFAULT_ONSET_SKIP_MS: u32 = 100;
+pub const MAX_ONSET_SKIP_MS: u32 = 500;
+
+/// オンセットとみなす速いエンベロープと遅いエンベロープの比
+pub const ONSET_RATIO: f32 = 2.0;
+
+/// オンセット検出の速いピークエンベロープの減衰と、遅いエンベロープの時定数（ミリ秒）
+/// 速い側の減衰はE1（41Hz）の半周期より長くし、低音の波形の谷で誤検出しないようにする
+pub const ONSET_FAST_RELEASE_MS: f32 = 20.0;
+pub const ONSET_SLOW_MS: f32 = 30.0;
+
+/// 続けてオンセットとみなさない最小間隔（ミリ秒）
+pub const ONSET_MIN_INTERVAL_MS: f32 = 50.0;
+
+/// 電源ハムのノッチを入れる倍音数（基本波を含む、50Hz系なら50〜200Hz）
+pub const HUM_HARMONICS: usize = 4;
+
+/// ハムのノッチの帯域幅（Hz、隣の音（A2=110Hzなど）を削らないよう狭くする）
+pub const HUM_NOTCH_BANDWIDTH_HZ: f32 = 2.0;
+
+/// 電源周波数とみなすパワー比（もう一方の系列と中間の参照周波数に対する比）
+pub const HUM_DETECT_RATIO: f32 = 4.0;
+
+/// DC除去の高域通過フィルタのカットオフ（Hz、5弦ベースのB0より十分低く）
+pub const DC_BLOCK_CUTOFF_HZ: f32 = 10.0;
@@ -1,24 +1,33 @@
-use rustfft::num_complex::Complex;
-use rustfft::FftPlanner;
 use serde::Serialize;
 use std::sync::atomic::Ordering;
-use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
 use tauri::image::Image;
 use tauri::Emitter;
 
+use crate::audio::RingConsumer;
 use crate::constants::{
-    determine_color_with_hysteresis, TuningColor, CHANNEL_MODE, CUSTOM_PITCH, DROP_TUNING_ENABLED,
-    DROP_TUNING_NOTE, FFT_SIZE, GUITAR_FREQUENCIES, LAST_TUNING_INFO, PITCH_MODE, RMS_THRESHOLD,
-    STOP_FLAG, THRESHOLD_RATIO, TRAY_ICON_MODE, TRAY_ICON_STATE, TUNING_GREEN_THRESHOLD,
-    TUNING_RED_THRESHOLD, TUNING_SHIFT,
+    determine_color_with_hysteresis, TunerMode, TuningColor, TuningInfo, ACTIVE_TEMPERAMENT,
+    ACTIVE_TUNING, CHANNEL_MODE, CHROMATIC_MAX_FREQUENCY, CHROMATIC_MIN_FREQUENCY,
+    DROP_TUNING_ENABLED, DROP_TUNING_NOTE, FFT_SIZE, HOP_SIZE, HUM_FILTER, INSTRUMENT,
+    KALMAN_MEASUREMENT_NOISE, KALMAN_PROCESS_NOISE, LAST_TUNING_INFO, MAINS_FREQUENCY,
+    MIN_HOP_SIZE, NOISE_CALIBRATION_MS, NOISE_GATE, NOISE_PROFILE, ONSET_SKIP_MS, PITCH_DETECTOR,
+    PITCH_SMOOTHING, SHORT_FFT_SIZE, SHORT_WINDOW_MIN_FREQUENCY, SMOOTHING_EMA_ALPHA, STOP_FLAG,
+    STREAM_STALLED, STREAM_STALL_TIMEOUT_MS, STRING_TARGETS_GENERATION, STROBE_MAX_HOP_SIZE,
+    THRESHOLD_RATIO, TRAY_ICON_MODE, TRAY_ICON_STATE, TUNER_MODE, TUNING_GREEN_THRESHOLD,
+    TUNING_RED_THRESHOLD,
 };
-use crate::dsp::frequency::{
-    calculate_frequency_bins, calculate_noise_floor, detect_guitar_fundamental,
-    gaussian_interpolation, is_guitar_frequency,
-};
-use crate::dsp::window::apply_blackman_harris_window;
+use crate::dsp::detector::{PitchDetector, PitchDetectorKind, SearchRange};
+use crate::dsp::lowfreq::LowFrequencyDetector;
+use crate::dsp::noise::NoiseTracker;
+use crate::dsp::onset::{OnsetDetector, OnsetWindow};
+use crate::dsp::polyphonic::{StringReading, StrumAnalyzer};
+use crate::dsp::prefilter::{HumFilterMode, PreFilter};
+use crate::dsp::smoothing::{PitchSmoother, PitchSmoothing, SmoothingConfig};
+use crate::dsp::strobe::StrobeTracker;
+use crate::instrument::Instrument;
+use crate::pitch::{cents_from, nearest_target_index, PitchReference, StringTarget};
+use crate::tuning::StringPitch;
 
 #[derive(Debug, Serialize, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
@@ -35,6 +44,68 @@ struct NoteInfoEventPayload {
     cent: f32,
     target_freq: f32,
     tuning_status: FrontendTuningStatus,
+    /// ピークとノイズレベルの比と倍音の一致度から求めた信頼度（0.0〜1.0）
+    confidence: f32,
+    /// ピークとノイズレベルの比（スペクトルを使わない検出器ではNone）
+    peak_to_noise: Option<f32>,
+    mode: &'static str,
+    /// 解析したフレームの終端（ストリーム開始からの累計フレーム数）
+    sample_position: u64,
+    /// 解析に使った窓長（サンプル数）
+    window_size: usize,
+    /// 平滑化前の検出周波数
+    raw_frequency: f32,
+    /// 目標とした弦のインデックス（チューニングの弦の順、クロマチックモードではNone）
+    string_index: Option<usize>,
+    sample_rate: usize,
+    /// 解析に使ったFFT長（スペクトルを使わない検出器ではNone）
+    fft_size: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct StrobeEventPayload {
+    name: String,
+    frequency: f32,
+    target_freq: f32,
+    cent: f32,
+    /// 目標周波数に対する位相速度（rad/s、正なら高い）
+    phase_velocity: f32,
+    confidence: f32,
+    sample_position: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct OnsetEventPayload {
+    /// オンセットの位置（ストリーム開始からの累計フレーム数）
+    sample_position: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct NoiseCalibratedEventPayload {
+    /// 信号レベルのゲート（RMS）
+    gate: f32,
+    /// ピークとノイズレベルの比率の閾値
+    threshold_ratio: f32,
+    /// 測定したノイズのRMS（dB）
+    noise_level: f32,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct StrumEventPayload {
+    sample_position: u64,
+    strings: Vec<StringReading>,
+}
+
+/// 入力ストリームの状態（stream_stateイベント用）
+#[derive(Debug, Serialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum StreamState {
+    Active,
+    Stalled,
 }
 
 fn classify_tuning_status(cent: f32) -> FrontendTuningStatus {
@@ -48,89 +119,126 @@ fn classify_tuning_status(cent: f32) -> FrontendTuningStatus {
     }
 }
 
-/// 現在の設定に基づいて基準A4周波数を取得（目標周波数計算用）
-fn get_effective_a4() -> f32 {
-    match PITCH_MODE.load(Ordering::SeqCst) {
-        0 => 440.0,                         // Standard
-        1 => *CUSTOM_PITCH.read().unwrap(), // Custom
-        2 => {
-            // Shift: A4を半音単位でシフト
-            let shift = TUNING_SHIFT.load(Ordering::SeqCst);
-            440.0 * 2.0_f32.powf(shift as f32 / 12.0)
+/// 6弦ドロップ時の目標音を取得（ドロップ無効ならNone）
+fn get_drop_target_note() -> Option<&'static str> {
+    if DROP_TUNING_ENABLED.load(Ordering::SeqCst) {
+        let note = DROP_TUNING_NOTE.load(Ordering::SeqCst);
+        match note {
+            0 => Some("D2"),
+            1 => Some("C#2"),
+            2 => Some("C2"),
+            3 => Some("B1"),
+            _ => None,
         }
-        _ => 440.0,
+    } else {
+        None
     }
 }
 
-/// 音名判定用のA4周波数を取得（customモードのみ考慮、shiftは考慮しない）
-fn get_custom_a4_for_note() -> f32 {
-    match PITCH_MODE.load(Ordering::SeqCst) {
-        1 => *CUSTOM_PITCH.read().unwrap(), // Custom
-        _ => 440.0,
+/// 現在の平滑化の設定を取得
+fn get_smoothing_config() -> SmoothingConfig {
+    SmoothingConfig {
+        kind: PitchSmoothing::from_u32(PITCH_SMOOTHING.load(Ordering::SeqCst)),
+        ema_alpha: *SMOOTHING_EMA_ALPHA.read().unwrap(),
+        process_noise: *KALMAN_PROCESS_NOISE.read().unwrap(),
+        measurement_noise: *KALMAN_MEASUREMENT_NOISE.read().unwrap(),
     }
 }
 
-/// 6弦の目標周波数を取得（ドロップ考慮）
-fn get_string6_target_freq() -> f32 {
-    if DROP_TUNING_ENABLED.load(Ordering::SeqCst) {
-        let note = DROP_TUNING_NOTE.load(Ordering::SeqCst);
-        match note {
-            0 => 73.42, // D2
-            1 => 69.30, // C#2
-            2 => 65.41, // C2
-            3 => 61.74, // B1
-            _ => 82.41, // E2 (default)
+/// アクティブなチューニングの各弦の目標を取得（ドロップ・音律・基準ピッチ・オフセットを考慮）
+pub fn get_string_targets(instrument: Instrument, reference: &PitchReference) -> Vec<StringTarget> {
+    let mut strings = ACTIVE_TUNING.read().unwrap().strings.clone();
+
+    // 6弦（最低音弦）をドロップ設定で更新（ギターのみ）
+    if instrument.supports_drop_tuning() {
+        if let (Some(lowest), Some(drop_note)) = (strings.first_mut(), get_drop_target_note()) {
+            *lowest = StringPitch::Note(drop_note.to_string());
         }
-    } else {
-        82.41 // E2
+    }
+
+    reference.string_targets(&strings, &ACTIVE_TEMPERAMENT.read().unwrap())
+}
+
+/// 最も近い弦のチューニング上の位置（frequenciesはtargetsの目標周波数）
+fn nearest_string_index(freq: f32, targets: &[StringTarget], frequencies: &[f32]) -> Option<usize> {
+    nearest_target_index(freq, frequencies).map(|i| targets[i].index)
+}
+
+/// クロマチックモード: 最も近い音律の音を目標として音名とセント値を計算
+fn calculate_chromatic_note_info(freq: f32, reference: &PitchReference) -> (String, f32, f32) {
+    ACTIVE_TEMPERAMENT
+        .read()
+        .unwrap()
+        .nearest_note(freq, reference.note_a4())
+}
+
+/// 最も近い弦の目標の音名・目標周波数と、その目標に対するセント値を計算
+fn calculate_note_info(
+    freq: f32,
+    targets: &[StringTarget],
+    reference: &PitchReference,
+) -> (String, f32, f32) {
+    let nearest = targets.iter().min_by(|a, b| {
+        let diff_a = (freq - a.frequency).abs();
+        let diff_b = (freq - b.frequency).abs();
+        diff_a.partial_cmp(&diff_b).unwrap()
+    });
+    match nearest {
+        Some(target) => (
+            target.name.clone(),
+            target.frequency,
+            cents_from(freq, target.frequency),
+        ),
+        None => calculate_chromatic_note_info(freq, reference),
     }
 }
 
-/// 周波数から音名とセント値を計算
-fn calculate_note_info(freq: f32) -> (String, f32, f32) {
-    const NOTE_NAMES: [&str; 12] = [
-        "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
-    ];
-    // 音名判定用のA4（customモードのみ考慮、shiftは考慮しない）
-    let a4_for_note = get_custom_a4_for_note();
-
-    // A4からの半音数を計算
-    let semitones_from_a4 = 12.0 * (freq / a4_for_note).log2();
-    let nearest_semitone = semitones_from_a4.round() as i32;
-    let cents = (semitones_from_a4 - nearest_semitone as f32) * 100.0;
-
-    // 音名とオクターブを計算（A4 = MIDIノート69）
-    let midi_note = 69 + nearest_semitone;
-    let note_index = ((midi_note % 12) + 12) % 12;
-    let octave = (midi_note / 12) - 1;
-    let note_name = format!("{}{}", NOTE_NAMES[note_index as usize], octave);
-
-    // 目標周波数の計算（こちらはshiftも考慮）
-    let a4_for_target = get_effective_a4();
-
-    // 6弦の周波数を動的に取得
-    let string6_freq = get_string6_target_freq();
-    // 基準ピッチのシフトを考慮したギター周波数リストを作成
-    let shift_ratio = a4_for_target / 440.0;
-
-    let mut target_freqs = GUITAR_FREQUENCIES.to_vec();
-    // 6弦を更新
-    target_freqs[0] = string6_freq;
-
-    // 全体をシフト
-    let shifted_targets: Vec<f32> = target_freqs.iter().map(|&f| f * shift_ratio).collect();
-
-    let target_freq = shifted_targets
-        .iter()
-        .min_by(|a, b| {
-            let diff_a = (freq - *a).abs();
-            let diff_b = (freq - *b).abs();
-            diff_a.partial_cmp(&diff_b).unwrap()
-        })
-        .copied()
-        .unwrap_or(freq);
-
-    (note_name, target_freq, cents)
+/// 弦の目標と探索条件のキャッシュ（設定が変わったときだけ作り直し、解析ごとの確保を避ける）
+struct TargetCache {
+    /// 作成時の設定の世代・チューナーモード・楽器（Noneなら未作成）
+    key: Option<(u64, TunerMode, Instrument)>,
+    reference: PitchReference,
+    targets: Vec<StringTarget>,
+    range: SearchRange,
+}
+
+impl TargetCache {
+    fn new() -> Self {
+        Self {
+            key: None,
+            reference: PitchReference::default(),
+            targets: Vec::new(),
+            range: SearchRange::unrestricted(CHROMATIC_MIN_FREQUENCY, CHROMATIC_MAX_FREQUENCY),
+        }
+    }
+
+    /// 設定・モード・楽器が変わっていれば目標と探索条件を作り直す
+    fn refresh(&mut self, tuner_mode: TunerMode, instrument: Instrument) {
+        let key = Some((
+            STRING_TARGETS_GENERATION.load(Ordering::SeqCst),
+            tuner_mode,
+            instrument,
+        ));
+        if self.key == key {
+            return;
+        }
+        self.key = key;
+        self.reference = PitchReference::current();
+        if tuner_mode == TunerMode::Chromatic {
+            self.targets.clear();
+            self.range =
+                SearchRange::unrestricted(CHROMATIC_MIN_FREQUENCY, CHROMATIC_MAX_FREQUENCY);
+        } else {
+            self.targets = get_string_targets(instrument, &self.reference);
+            let (min_freq, max_freq) = instrument.frequency_range();
+            self.range = SearchRange::for_targets(
+                self.targets.iter().map(|t| t.frequency).collect(),
+                min_freq,
+                max_freq,
+                instrument.tolerance(),
+            );
+        }
+    }
 }
 
 /// 5x7ピクセルの大きめビットマップフォント（A-G, #）
@@ -622,20 +730,52 @@ fn reset_tray_icon(app_handle: &tauri::AppHandle) {
 /// 周波数解析スレッドを起動
 pub fn run_analysis_thread(
     app_handle: tauri::AppHandle,
-    buffer: Arc<Mutex<Vec<f32>>>,
+    mut input: RingConsumer,
     sample_rate: usize,
     channels: usize,
 ) {
-    let buffer_size = FFT_SIZE * channels;
-
     // リセット判定用の定数（秒）
     const RESET_TIMEOUT_SECS: u64 = 3;
+    // 新しいサンプルを待つ最大時間（ミリ秒、停止フラグ・無音リセット・ストリーム停止の確認間隔）
+    const MAX_WAIT_MS: u64 = 100;
 
     thread::spawn(move || {
-        let mut planner = FftPlanner::<f32>::new();
+        // ピッチ検出器（設定変更時に作り直す）
+        let mut detector_kind = PitchDetectorKind::from_u32(PITCH_DETECTOR.load(Ordering::SeqCst));
+        let mut detector = detector_kind.create();
+        // ベース用の低音域検出器（楽器がベースの場合は設定に関わらずこちらを使う）
+        let mut low_freq_detector = LowFrequencyDetector::new();
+        // ストロークモード用の全弦同時解析
+        let mut strum_analyzer = StrumAnalyzer::new();
+        // ストロボモード用の位相追跡
+        let mut strobe_tracker = StrobeTracker::new();
+        // 窓かけ前の前処理（DC除去 + 電源ハムのノッチ）
+        let mut prefilter = PreFilter::new(sample_rate);
+        // ノイズプロファイルの測定と追従
+        let mut noise_tracker = NoiseTracker::new();
+        // 撥弦の検出と、前の音・アタックを解析窓に含めないための範囲
+        let mut onset_detector = OnsetDetector::new(sample_rate);
+        let mut onset_window = OnsetWindow::new();
+
+        // モード切り替え検出用
+        let mut last_tuner_mode = TunerMode::from_u32(TUNER_MODE.load(Ordering::SeqCst));
+
+        // 新しく届いたサンプル（インターリーブ、再利用）
+        let mut interleaved: Vec<f32> = Vec::with_capacity(input.capacity());
+        // 最新FFT_SIZEサンプルのモノラル信号と、そのうち実際に受信済みのサンプル数
+        // 受信前の部分は無音として扱い、最初のホップから解析を始める
+        let mut mono = vec![0.0f32; FFT_SIZE];
+        let mut filled = 0usize;
+        // 前回の解析以降に届いたフレーム数
+        let mut pending = 0usize;
+        // ストリーム停止を通知済みか
+        let mut stalled = false;
+        STREAM_STALLED.store(false, Ordering::SeqCst);
 
         // 周波数安定化用のバッファ
-        let mut freq_history: Vec<f32> = Vec::with_capacity(10);
+        let mut smoother = PitchSmoother::new(get_smoothing_config());
+        // 弦の目標と探索条件（設定が変わったときだけ作り直す）
+        let mut target_cache = TargetCache::new();
 
         // 最後に有効な音を検出した時刻
         let mut last_valid_sound_time: Option<Instant> = None;
@@ -648,7 +788,17 @@ pub fn run_analysis_thread(
                 println!("解析スレッド終了");
                 break;
             }
-            thread::sleep(Duration::from_millis(50));
+
+            // 次のホップ分のフレームが届くまで待つ（届くまでは起きない）
+            let tuner_mode = TunerMode::from_u32(TUNER_MODE.load(Ordering::SeqCst));
+            let mut hop = (HOP_SIZE.load(Ordering::SeqCst) as usize).clamp(MIN_HOP_SIZE, FFT_SIZE);
+            if tuner_mode == TunerMode::Strobe {
+                hop = hop.min(STROBE_MAX_HOP_SIZE);
+            }
+            input.wait_for_frames(
+                hop.saturating_sub(pending),
+                Duration::from_millis(MAX_WAIT_MS),
+            );
 
             // リセット判定: 有効な音が一定時間検出されなかったらリセット
             if let Some(last_time) = last_valid_sound_time {
@@ -657,8 +807,11 @@ pub fn run_analysis_thread(
                     let _ = app_handle.emit("reset", ());
                     // トレイアイコンをリセット
                     reset_tray_icon(&app_handle);
-                    // 履歴をクリア
-                    freq_history.clear();
+                    // 履歴と最後の検出結果をクリア
+                    smoother.reset();
+                    if let Ok(mut info) = LAST_TUNING_INFO.lock() {
+                        *info = TuningInfo::default();
+                    }
                     is_reset = true;
                     println!(
                         "リセットイベント送信: {}秒間音が検出されませんでした",
@@ -667,155 +820,371 @@ pub fn run_analysis_thread(
                 }
             }
 
-            let samples: Vec<f32>;
-            {
-                let buf = buffer.lock().unwrap();
-                if buf.len() < buffer_size {
-                    continue;
+            // 前回以降に届いたサンプルだけを読み出す（新しい音が無ければ解析しない）
+            interleaved.clear();
+            let read = input.read_new(&mut interleaved);
+            if read.dropped_frames > 0 {
+                println!(
+                    "解析が遅れたため {} フレームを破棄（{} フレーム目まで受信）",
+                    read.dropped_frames, read.end_frame
+                );
+            }
+            if read.frames == 0 {
+                // 一定時間新しいサンプルが届かなければ、無音とは別の停止状態として通知
+                let silent_for = read.timestamp.elapsed();
+                if !stalled && silent_for > Duration::from_millis(STREAM_STALL_TIMEOUT_MS) {
+                    stalled = true;
+                    STREAM_STALLED.store(true, Ordering::SeqCst);
+                    let _ = app_handle.emit("stream_state", StreamState::Stalled);
+                    // 再開後の音と途切れる前の音が混ざらないよう履歴を捨てる
+                    mono.fill(0.0);
+                    filled = 0;
+                    pending = 0;
+                    prefilter.reset();
+                    onset_detector.reset();
+                    onset_window.reset();
+                    smoother.reset();
+                    println!(
+                        "入力ストリーム停止: {}ms間サンプルが届いていません",
+                        silent_for.as_millis()
+                    );
                 }
-                samples = buf.clone();
+                continue;
+            }
+            if stalled {
+                stalled = false;
+                STREAM_STALLED.store(false, Ordering::SeqCst);
+                let _ = app_handle.emit("stream_state", StreamState::Active);
+                println!("入力ストリーム再開");
             }
 
             // チャンネルモードを取得
             let channel_mode = CHANNEL_MODE.load(Ordering::SeqCst);
 
-            // モノラル化（チャンネルモードに応じて選択）
-            let mono: Vec<f32> = if channels == 2 {
-                samples
-                    .chunks(2)
-                    .map(|chunk| {
-                        if chunk.len() == 2 {
-                            match channel_mode {
-                                0 => chunk[0],                    // 左チャンネル
-                                1 => chunk[1],                    // 右チャンネル
-                                _ => (chunk[0] + chunk[1]) / 2.0, // 両方の平均
-                            }
-                        } else {
-                            chunk[0]
-                        }
-                    })
-                    .collect()
+            // モノラル化（チャンネルモードに応じて選択）して履歴の末尾に追加
+            let new_frames = read.frames.min(FFT_SIZE);
+            mono.copy_within(new_frames.., 0);
+            let frames = interleaved
+                .chunks_exact(channels)
+                .skip(read.frames - new_frames);
+            for (dst, frame) in mono[FFT_SIZE - new_frames..].iter_mut().zip(frames) {
+                *dst = if channels == 2 {
+                    match channel_mode {
+                        0 => frame[0],                    // 左チャンネル
+                        1 => frame[1],                    // 右チャンネル
+                        _ => (frame[0] + frame[1]) / 2.0, // 両方の平均
+                    }
+                } else {
+                    frame[0]
+                };
+            }
+            // 新しいサンプルだけを前処理（ハムの判定は音が検出されていない間だけ行う）
+            let hum_filter = HumFilterMode::from_u32(HUM_FILTER.load(Ordering::SeqCst));
+            prefilter.process(&mut mono[FFT_SIZE - new_frames..], hum_filter, is_reset);
+            let mains = match hum_filter {
+                HumFilterMode::Off => None,
+                _ => prefilter.mains_frequency(),
+            };
+            MAINS_FREQUENCY.store(mains.unwrap_or(0), Ordering::SeqCst);
+
+            // 撥弦の検出: 新しい音では前の音の履歴を捨て、アタックの間は解析しない
+            // （ノイズキャリブレーション中は無音を測っているので検出しない）
+            let gate = *NOISE_GATE.read().unwrap();
+            let calibrating =
+                noise_tracker.is_calibrating() || NOISE_CALIBRATION_MS.load(Ordering::SeqCst) > 0;
+            let onset = if calibrating {
+                None
             } else {
-                samples.clone()
+                onset_detector.process(&mono[FFT_SIZE - new_frames..], gate)
             };
+            if let Some(offset) = onset {
+                let onset_frame = read.end_frame - (new_frames - offset) as u64;
+                let skip_ms = ONSET_SKIP_MS.load(Ordering::SeqCst) as u64;
+                onset_window.mark(onset_frame, skip_ms * sample_rate as u64 / 1000);
+                smoother.reset();
+                let payload = OnsetEventPayload {
+                    sample_position: onset_frame,
+                };
+                let _ = app_handle.emit("onset", payload);
+            }
+            filled = (filled + read.frames).min(FFT_SIZE);
+            pending += read.frames;
 
-            if mono.len() < FFT_SIZE {
+            // ホップサイズ分の新しいフレームが届いたときだけ解析する（同じ音を重複して解析しない）
+            // 入力コールバックの1回分や解析の遅れで複数ホップ分が届いた場合は、最新の窓だけを解析する
+            // （途中のホップ位置の窓は個別に解析しないが、そのサンプルは最新の窓に含まれる）
+            if pending < hop {
                 continue;
             }
+            let hop_frames = std::mem::take(&mut pending);
+            // 解析結果に付けるサンプル位置（解析したフレームの終端）
+            let sample_position = read.end_frame;
 
-            // 信号レベルチェック
+            // 信号レベルチェック（受信済みの部分のみ）
+            let received = &mono[FFT_SIZE - filled..];
             let rms: f32 =
-                (mono.iter().take(FFT_SIZE).map(|x| x * x).sum::<f32>() / FFT_SIZE as f32).sqrt();
+                (received.iter().map(|x| x * x).sum::<f32>() / received.len() as f32).sqrt();
 
             // RMSレベルをフロントエンドに送信（dBスケールで-80〜0dB → 0〜1）
             let db = 20.0 * rms.max(0.0000001).log10();
             let level = ((db + 80.0) / 80.0).clamp(0.0, 1.0);
             let _ = app_handle.emit("input_level", level);
 
-            // RMS閾値（ノイズフロア以下はスキップ）
-            if rms < RMS_THRESHOLD {
-                continue;
+            // ノイズキャリブレーションの要求を受け付ける
+            let calibration_ms = NOISE_CALIBRATION_MS.swap(0, Ordering::SeqCst);
+            if calibration_ms > 0 {
+                noise_tracker.start_calibration(calibration_ms as usize * sample_rate / 1000);
+                onset_detector.reset();
+                smoother.reset();
+                println!("ノイズキャリブレーション開始: {}ms", calibration_ms);
             }
 
-            // Blackman-Harris窓を適用
-            let mut windowed: Vec<f32> = mono.iter().take(FFT_SIZE).cloned().collect();
-            apply_blackman_harris_window(&mut windowed);
-
-            // 2倍のゼロパディング（周波数分解能を2倍に向上）
-            let padded_size = FFT_SIZE * 2;
-            let mut input: Vec<Complex<f32>> = vec![Complex { re: 0.0, im: 0.0 }; padded_size];
-            for (i, &v) in windowed.iter().enumerate() {
-                input[i] = Complex { re: v, im: 0.0 };
+            // キャリブレーション中は無音として測定し、ピッチ検出しない
+            // 受信済みのサンプルが窓長に満たない間は、無音の埋め草で過小評価しないよう測らない
+            if noise_tracker.is_calibrating() {
+                if filled == FFT_SIZE {
+                    if let Some(result) = noise_tracker.calibrate(&mono, hop_frames, sample_rate) {
+                        let payload = NoiseCalibratedEventPayload {
+                            gate: result.gate,
+                            threshold_ratio: result.threshold_ratio,
+                            noise_level: 20.0 * result.profile.rms().max(0.0000001).log10(),
+                        };
+                        *NOISE_GATE.write().unwrap() = result.gate;
+                        *THRESHOLD_RATIO.lock().unwrap() = result.threshold_ratio;
+                        *NOISE_PROFILE.write().unwrap() = Some(result.profile);
+                        println!(
+                            "ノイズキャリブレーション完了: ゲート {:.5}, 閾値 {:.2}, ノイズ {:.1}dB",
+                            payload.gate, payload.threshold_ratio, payload.noise_level
+                        );
+                        let _ = app_handle.emit("noise_calibrated", payload);
+                    }
+                }
+                continue;
             }
 
-            let fft_padded = planner.plan_fft_forward(padded_size);
-            fft_padded.process(&mut input);
+            // 音が検出されていない間はノイズプロファイルとゲートをゆっくり追従させる
+            if is_reset && filled == FFT_SIZE {
+                if let Some(profile) = NOISE_PROFILE.write().unwrap().as_mut() {
+                    noise_tracker.adapt(profile, &mono, hop_frames, sample_rate);
+                    *NOISE_GATE.write().unwrap() = profile.gate();
+                }
+            }
 
-            // パワースペクトル（E2〜E4+αの範囲に限定）
-            let (min_bin, max_bin) = calculate_frequency_bins(sample_rate, padded_size);
-            if max_bin <= min_bin {
+            // RMSゲート（ノイズフロア以下はスキップ）
+            if rms < *NOISE_GATE.read().unwrap() {
                 continue;
             }
-            let spectrum: Vec<f32> = input[min_bin..max_bin].iter().map(|c| c.norm()).collect();
-
-            // ノイズフロアを計算
-            let noise_floor = calculate_noise_floor(&spectrum);
-
-            // ピーク検出
-            if let Some((max_idx, max_val)) = spectrum
-                .iter()
-                .enumerate()
-                .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
-            {
-                // ガウシアン補間でより正確な周波数を求める
-                let freq =
-                    gaussian_interpolation(&spectrum, max_idx, min_bin, sample_rate, padded_size);
-
-                // 現在の閾値を取得
-                let threshold_ratio = *THRESHOLD_RATIO.lock().unwrap();
-
-                // ピークがノイズフロアの閾値倍以上なら有効な信号
-                if *max_val > noise_floor * threshold_ratio {
-                    // 生の周波数をリアルタイム送信（閾値を超えた場合のみ）
-                    let _ = app_handle.emit("raw_frequency", freq);
-
-                    // 基音検出を試みる
-                    if let Some(adjusted_freq) = detect_guitar_fundamental(
-                        freq,
-                        &spectrum,
-                        min_bin,
-                        max_bin,
-                        padded_size,
-                        sample_rate,
-                        noise_floor,
-                        *max_val,
-                    ) {
-                        if is_guitar_frequency(adjusted_freq) {
-                            // 履歴に追加
-                            freq_history.push(adjusted_freq);
-                            if freq_history.len() > 5 {
-                                freq_history.remove(0);
-                            }
 
-                            // 2サンプル以上あれば中央値を計算して送信
-                            if freq_history.len() >= 2 {
-                                let mut sorted = freq_history.clone();
-                                sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
-                                let median_freq = sorted[sorted.len() / 2];
-
-                                // チューニング情報を計算
-                                let (note_name, target_freq, cents) =
-                                    calculate_note_info(median_freq);
-
-                                // frequencyイベントemit
-                                let _ = app_handle.emit("frequency", median_freq);
+            // 撥弦後は、アタック（音程が高めに揺れる）の後のサンプルが窓長分たまるまでその窓長で解析しない
+            // （前の音やアタックが窓に混ざった推定を出さない。短い窓から先に使えるようになる）
+            let short_window_ready = onset_window.allows(sample_position, SHORT_FFT_SIZE);
+            let long_window_ready = onset_window.allows(sample_position, FFT_SIZE);
+            if !short_window_ready {
+                continue;
+            }
 
-                                // トレイアイコンも必ず同期して更新
-                                update_tray_icon(&app_handle, cents, &note_name);
+            // 検出アルゴリズムの切り替えを反映
+            let kind = PitchDetectorKind::from_u32(PITCH_DETECTOR.load(Ordering::SeqCst));
+            if kind != detector_kind {
+                detector = kind.create();
+                detector_kind = kind;
+                smoother.reset();
+                println!("Pitch detector switched to: {}", kind.name());
+            }
 
-                                // 有効な音を検出したので時刻を更新
-                                last_valid_sound_time = Some(Instant::now());
-                                is_reset = false;
+            // チューナーモードの切り替えを反映
+            if tuner_mode != last_tuner_mode {
+                last_tuner_mode = tuner_mode;
+                smoother.reset();
+            }
+            let instrument = Instrument::from_u32(INSTRUMENT.load(Ordering::SeqCst));
+            target_cache.refresh(tuner_mode, instrument);
+            let TargetCache {
+                reference,
+                targets,
+                range,
+                ..
+            } = &target_cache;
+
+            // ストロークモード: 全弦のずれをまとめてstrumイベントで送信
+            if tuner_mode == TunerMode::Strum {
+                // 全弦の解析には長い窓を使う
+                if !long_window_ready {
+                    continue;
+                }
+                let threshold_ratio = *THRESHOLD_RATIO.lock().unwrap();
+                let readings = strum_analyzer.analyze(
+                    &mono[..FFT_SIZE],
+                    sample_rate,
+                    &range.targets,
+                    threshold_ratio,
+                );
+                if readings.iter().any(|r| r.detected_freq.is_some()) {
+                    let payload = StrumEventPayload {
+                        sample_position,
+                        strings: readings,
+                    };
+                    let _ = app_handle.emit("strum", payload);
+                    last_valid_sound_time = Some(read.timestamp);
+                    is_reset = false;
+                }
+                continue;
+            }
 
-                                // グローバル変数を更新
-                                if let Ok(mut info) = LAST_TUNING_INFO.lock() {
-                                    info.note_name = note_name.clone();
-                                    info.frequency = median_freq;
-                                    info.cents = cents;
-                                }
+            // ピッチ検出（有効な信号がなければスキップ）
+            let use_low_frequency_path =
+                tuner_mode != TunerMode::Chromatic && instrument.uses_low_frequency_path();
+            let active_detector: &mut dyn PitchDetector = if use_low_frequency_path {
+                &mut low_freq_detector
+            } else {
+                detector.as_mut()
+            };
 
-                                // トレイのツールチップも更新
-                                update_tray_tooltip(&app_handle, &note_name, median_freq, cents);
+            // 多重解像度: まず短い窓で粗く推定し、高い音ならその結果を使う
+            // 低い音（と推定できなかった場合）だけ長い窓で解析し直す
+            let mut window_size = SHORT_FFT_SIZE;
+            let mut estimate = None;
+            if !use_low_frequency_path && range.max_freq >= SHORT_WINDOW_MIN_FREQUENCY {
+                estimate =
+                    active_detector.detect(&mono[FFT_SIZE - SHORT_FFT_SIZE..], sample_rate, range);
+            }
+            let is_high = estimate
+                .and_then(|e| e.fundamental)
+                .is_some_and(|f| f >= SHORT_WINDOW_MIN_FREQUENCY);
+            if !is_high {
+                if !long_window_ready {
+                    continue;
+                }
+                window_size = FFT_SIZE;
+                estimate = active_detector.detect(&mono, sample_rate, range);
+            }
+            let Some(estimate) = estimate else {
+                continue;
+            };
 
-                                let payload = NoteInfoEventPayload {
-                                    name: note_name.clone(),
-                                    cent: cents,
+            // 生の周波数をリアルタイム送信（有効な信号の場合のみ）
+            let _ = app_handle.emit("raw_frequency", estimate.raw_frequency);
+
+            // 基音が特定できた場合のみ処理
+            if let Some(adjusted_freq) = estimate.fundamental {
+                // ギターモードは楽器の弦の目標付近の音のみ、クロマチックモードは解析範囲内の音を採用
+                if range.matches(adjusted_freq) {
+                    // ストロボモード: 中央値で平滑化せず、位相の傾きからずれを毎回送信
+                    if tuner_mode == TunerMode::Strobe {
+                        let (note_name, target_freq, _) =
+                            calculate_note_info(adjusted_freq, targets, reference);
+                        // 位相の追跡も撥弦後のサンプルだけで行う
+                        let frames = filled.min(onset_window.available(sample_position));
+                        if let Some(phase) = strobe_tracker.measure(
+                            &mono[FFT_SIZE - frames..],
+                            sample_rate,
+                            adjusted_freq,
+                        ) {
+                            let cents = cents_from(phase.frequency, target_freq);
+                            let payload = StrobeEventPayload {
+                                name: note_name.clone(),
+                                frequency: phase.frequency,
+                                target_freq,
+                                cent: cents,
+                                // 検波周波数（粗い推定値）に対する位相速度を目標基準に換算
+                                phase_velocity: phase.phase_velocity
+                                    + 2.0 * std::f32::consts::PI * (adjusted_freq - target_freq),
+                                confidence: phase.coherence,
+                                sample_position,
+                            };
+                            let _ = app_handle.emit("strobe", payload);
+                            update_tray_icon(&app_handle, cents, &note_name);
+                            last_valid_sound_time = Some(read.timestamp);
+                            is_reset = false;
+
+                            // ギター・クロマチックモードと同様にグローバル変数とツールチップを更新
+                            if let Ok(mut info) = LAST_TUNING_INFO.lock() {
+                                *info = TuningInfo {
+                                    note_name: note_name.clone(),
+                                    frequency: phase.frequency,
+                                    cents,
                                     target_freq,
-                                    tuning_status: classify_tuning_status(cents),
+                                    raw_frequency: adjusted_freq,
+                                    confidence: phase.coherence,
+                                    peak_to_noise: estimate.peak_to_noise,
+                                    string_index: nearest_string_index(
+                                        adjusted_freq,
+                                        targets,
+                                        &range.targets,
+                                    ),
+                                    sample_rate,
+                                    fft_size: estimate.fft_size,
+                                    window_size,
+                                    mode: tuner_mode.name(),
+                                    sample_position,
                                 };
-                                let _ = app_handle.emit("note_info", payload);
                             }
+                            update_tray_tooltip(&app_handle, &note_name, phase.frequency, cents);
+                        }
+                        continue;
+                    }
+
+                    // 平滑化して送信（音が変わった直後の1サンプル目は送らない）
+                    smoother.configure(get_smoothing_config());
+                    if let Some(smoothed_freq) = smoother.push(adjusted_freq) {
+                        // チューニング情報を計算
+                        let (note_name, target_freq, cents) = if tuner_mode == TunerMode::Chromatic
+                        {
+                            calculate_chromatic_note_info(smoothed_freq, reference)
+                        } else {
+                            calculate_note_info(smoothed_freq, targets, reference)
+                        };
+                        let string_index =
+                            nearest_string_index(smoothed_freq, targets, &range.targets);
+
+                        // frequencyイベントemit
+                        let _ = app_handle.emit("frequency", smoothed_freq);
+
+                        // トレイアイコンも必ず同期して更新
+                        update_tray_icon(&app_handle, cents, &note_name);
+
+                        // 有効な音を検出したので時刻を更新
+                        last_valid_sound_time = Some(read.timestamp);
+                        is_reset = false;
+
+                        // グローバル変数を更新
+                        if let Ok(mut info) = LAST_TUNING_INFO.lock() {
+                            *info = TuningInfo {
+                                note_name: note_name.clone(),
+                                frequency: smoothed_freq,
+                                cents,
+                                target_freq,
+                                raw_frequency: adjusted_freq,
+                                confidence: estimate.confidence,
+                                peak_to_noise: estimate.peak_to_noise,
+                                string_index,
+                                sample_rate,
+                                fft_size: estimate.fft_size,
+                                window_size,
+                                mode: tuner_mode.name(),
+                                sample_position,
+                            };
                         }
+
+                        // トレイのツールチップも更新
+                        update_tray_tooltip(&app_handle, &note_name, smoothed_freq, cents);
+
+                        let payload = NoteInfoEventPayload {
+                            name: note_name.clone(),
+                            cent: cents,
+                            target_freq,
+                            tuning_status: classify_tuning_status(cents),
+                            confidence: estimate.confidence,
+                            peak_to_noise: estimate.peak_to_noise,
+                            mode: tuner_mode.name(),
+                            sample_position,
+                            window_size,
+                            raw_frequency: adjusted_freq,
+                            string_index,
+                            sample_rate,
+                            fft_size: estimate.fft_size,
+                        };
+                        let _ = app_handle.emit("note_info", payload);
                     }
                 }
             }
@@ -0,0 +1,348 @@
+use std::sync::atomic::Ordering;
+
+use crate::constants::{
+    CONFIDENCE_FULL_SNR_DB, FFT_SIZE, HPS_HARMONICS, KAISER_BETA, NOISE_PROFILE, NOISE_REDUCTION,
+    PEAK_INTERPOLATOR, PEAK_REFINEMENT, THRESHOLD_RATIO, WINDOW_FUNCTION,
+};
+use crate::dsp::frequency::{
+    calculate_frequency_bins, detect_fundamental, is_target_frequency, PeakEstimator,
+    PeakInterpolator, PeakRefinement,
+};
+use crate::dsp::mcleod::McLeodDetector;
+use crate::dsp::noise::NoiseReduction;
+use crate::dsp::spectrum::Analyzer;
+use crate::dsp::window::WindowKind;
+use crate::dsp::yin::YinDetector;
+
+/// ピッチ検出の結果
+#[derive(Debug, Clone, Copy)]
+pub struct PitchEstimate {
+    /// 補正前の検出周波数（raw_frequencyイベント用）
+    pub raw_frequency: f32,
+    /// 基音として採用した周波数（基音が特定できなければNone）
+    pub fundamental: Option<f32>,
+    /// 推定の信頼度（0.0〜1.0）
+    pub confidence: f32,
+    /// ピークとノイズレベルの比（スペクトルを使わない検出器ではNone）
+    pub peak_to_noise: Option<f32>,
+    /// 解析に使ったFFT長（ゼロパディング後、スペクトルを使わない検出器ではNone）
+    pub fft_size: Option<usize>,
+}
+
+/// ピッチの探索条件
+#[derive(Debug, Clone)]
+pub struct SearchRange {
+    /// 解析範囲（Hz）
+    pub min_freq: f32,
+    pub max_freq: f32,
+    /// 目標周波数（空なら解析範囲内のすべての音が対象）
+    pub targets: Vec<f32>,
+    /// 目標周波数に対する許容誤差（比率）
+    pub tolerance: f32,
+}
+
+impl SearchRange {
+    /// 目標周波数を持たない探索条件（クロマチックモード用）
+    pub fn unrestricted(min_freq: f32, max_freq: f32) -> Self {
+        Self {
+            min_freq,
+            max_freq,
+            targets: Vec::new(),
+            tolerance: 0.0,
+        }
+    }
+
+    /// 目標周波数に合わせた探索条件（解析範囲は目標の許容誤差分まで広げる）
+    pub fn for_targets(targets: Vec<f32>, min_freq: f32, max_freq: f32, tolerance: f32) -> Self {
+        let lowest = targets.iter().copied().fold(f32::INFINITY, f32::min);
+        let highest = targets.iter().copied().fold(0.0, f32::max);
+        Self {
+            min_freq: min_freq.min(lowest * (1.0 - tolerance)),
+            max_freq: max_freq.max(highest * (1.0 + tolerance)),
+            targets,
+            tolerance,
+        }
+    }
+
+    /// 検出した周波数が探索対象かどうか
+    pub fn matches(&self, freq: f32) -> bool {
+        if self.targets.is_empty() {
+            (self.min_freq..=self.max_freq).contains(&freq)
+        } else {
+            is_target_frequency(freq, &self.targets, self.tolerance)
+        }
+    }
+}
+
+/// ピッチ検出アルゴリズムの共通インターフェース
+pub trait PitchDetector: Send {
+    /// モノラル信号から探索条件に沿ってピッチを推定（有効な信号がなければNone）
+    fn detect(
+        &mut self,
+        samples: &[f32],
+        sample_rate: usize,
+        range: &SearchRange,
+    ) -> Option<PitchEstimate>;
+}
+
+/// ピッチ検出アルゴリズムの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PitchDetectorKind {
+    Fft = 0,
+    Yin = 1,
+    McLeod = 2,
+}
+
+impl PitchDetectorKind {
+    /// 数値から変換（範囲外はFFT）
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => Self::Yin,
+            2 => Self::McLeod,
+            _ => Self::Fft,
+        }
+    }
+
+    /// 設定ファイル用の名前から変換
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "fft" => Some(Self::Fft),
+            "yin" => Some(Self::Yin),
+            "mcleod" => Some(Self::McLeod),
+            _ => None,
+        }
+    }
+
+    /// 設定ファイル用の名前
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Fft => "fft",
+            Self::Yin => "yin",
+            Self::McLeod => "mcleod",
+        }
+    }
+
+    /// 対応する検出器を生成
+    pub fn create(self) -> Box<dyn PitchDetector> {
+        match self {
+            Self::Fft => Box::new(FftPeakDetector::new()),
+            Self::Yin => Box::new(YinDetector::new()),
+            Self::McLeod => Box::new(McLeodDetector::new()),
+        }
+    }
+}
+
+/// FFTのピーク探索による検出器（窓関数 + ゼロパディング + ピーク補間）
+/// 入力の長さ（最大FFT_SIZE）を窓長として解析する
+pub struct FftPeakDetector {
+    /// 窓長ごとの解析器（2倍のゼロパディングで周波数分解能を2倍に向上）
+    analyzers: Vec<Analyzer>,
+}
+
+impl FftPeakDetector {
+    pub fn new() -> Self {
+        Self {
+            analyzers: Vec::new(),
+        }
+    }
+
+    /// 窓長に対応する解析器（初回のみ作成し、以降は再利用）
+    fn analyzer(&mut self, frame_size: usize, window_kind: WindowKind) -> &mut Analyzer {
+        let index = match self
+            .analyzers
+            .iter()
+            .position(|a| a.frame_size() == frame_size)
+        {
+            Some(index) => index,
+            None => {
+                self.analyzers
+                    .push(Analyzer::new(frame_size, 2, window_kind));
+                self.analyzers.len() - 1
+            }
+        };
+        let analyzer = &mut self.analyzers[index];
+        analyzer.set_window(window_kind);
+        analyzer
+    }
+}
+
+impl PitchDetector for FftPeakDetector {
+    fn detect(
+        &mut self,
+        samples: &[f32],
+        sample_rate: usize,
+        range: &SearchRange,
+    ) -> Option<PitchEstimate> {
+        // 最新のサンプルを窓長分だけ使う
+        let frame_size = samples.len().min(FFT_SIZE);
+        if frame_size == 0 {
+            return None;
+        }
+        let samples = &samples[samples.len() - frame_size..];
+        let window_kind = WindowKind::from_u32(
+            WINDOW_FUNCTION.load(Ordering::SeqCst),
+            *KAISER_BETA.read().unwrap(),
+        );
+        let analyzer = self.analyzer(frame_size, window_kind);
+        let padded_size = analyzer.padded_size();
+
+        let (min_bin, max_bin) =
+            calculate_frequency_bins(sample_rate, padded_size, range.min_freq, range.max_freq);
+        if max_bin <= min_bin {
+            return None;
+        }
+
+        // 振幅スペクトル（基音推定では解析範囲の倍音まで参照する）
+        analyzer.process(samples);
+
+        let bin_hz = sample_rate as f32 / padded_size as f32;
+        let noise_gain = analyzer.noise_gain();
+        let profile = NOISE_PROFILE.read().unwrap();
+        let reduction = NoiseReduction::from_u32(NOISE_REDUCTION.load(Ordering::SeqCst));
+
+        // ノイズプロファイルがあれば、ピーク探索の前にスペクトル上でノイズを除去
+        if let Some(p) = profile.as_ref() {
+            p.suppress(analyzer.magnitudes_mut(), bin_hz, noise_gain, reduction);
+        }
+        // ノイズフロアは除去後のスペクトルから計算（ピーク・基音の判定と信頼度で同じ基準を使う）
+        let noise_floor = analyzer.noise_floor(min_bin..max_bin);
+        // 除去しない場合だけ、binごとのノイズレベルをプロファイルから補う（除去した場合は除去済み）
+        let unsuppressed_profile = profile
+            .as_ref()
+            .filter(|_| reduction == NoiseReduction::Off);
+
+        // 解析範囲に限定したスペクトル
+        let magnitudes = analyzer.magnitudes();
+        let spectrum = &magnitudes[min_bin..max_bin];
+
+        // ピーク検出（除去していないノイズプロファイルがあれば、binごとのノイズレベルに対する比率が最大のbin）
+        let (max_idx, max_ratio) = spectrum
+            .iter()
+            .enumerate()
+            .map(|(i, &m)| {
+                let noise = match unsuppressed_profile {
+                    Some(p) => {
+                        noise_floor.max(p.level_at((min_bin + i) as f32 * bin_hz) * noise_gain)
+                    }
+                    None => noise_floor,
+                };
+                (i, m / noise.max(f32::MIN_POSITIVE))
+            })
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())?;
+        drop(profile);
+
+        // ピークがノイズレベルの閾値倍未満なら無効な信号
+        let threshold_ratio = *THRESHOLD_RATIO.lock().unwrap();
+        if max_ratio <= threshold_ratio {
+            return None;
+        }
+
+        // ピーク補間（ズームFFTが有効ならさらに精密化）でより正確な周波数を求める
+        let estimator = PeakEstimator {
+            interpolator: PeakInterpolator::from_u32(PEAK_INTERPOLATOR.load(Ordering::SeqCst)),
+            refinement: PeakRefinement::from_u32(PEAK_REFINEMENT.load(Ordering::SeqCst)),
+        };
+        let freq = estimator.frequency(analyzer, min_bin + max_idx, sample_rate);
+
+        // 倍音積スペクトルで基音を推定
+        let harmonic_limit = (max_bin * (HPS_HARMONICS + 1)).min(magnitudes.len());
+        let fundamental = detect_fundamental(
+            &magnitudes[..harmonic_limit],
+            min_bin,
+            max_bin,
+            sample_rate,
+            padded_size,
+            noise_floor,
+            range,
+        )
+        .map(|mut f| {
+            // 基音は選択した方法で倍音のピークを求め直し、倍音次数で割る
+            f.frequency =
+                estimator.frequency(analyzer, f.peak_bin, sample_rate) / f.harmonic as f32;
+            f
+        });
+
+        let harmonic_agreement = fundamental.map(|f| f.confidence).unwrap_or(0.0);
+        Some(PitchEstimate {
+            raw_frequency: freq,
+            fundamental: fundamental.map(|f| f.frequency),
+            confidence: spectral_confidence(harmonic_agreement, max_ratio),
+            peak_to_noise: Some(max_ratio),
+            fft_size: Some(padded_size),
+        })
+    }
+}
+
+/// スペクトルからの推定の信頼度（倍音の一致度にピークとノイズレベルの比の重みを掛ける）
+/// 比はCONFIDENCE_FULL_SNR_DBで1.0、0dB以下で0.0になるようdBで線形に重み付けする
+pub fn spectral_confidence(harmonic_agreement: f32, peak_to_noise: f32) -> f32 {
+    let snr_db = 20.0 * peak_to_noise.max(f32::MIN_POSITIVE).log10();
+    harmonic_agreement.clamp(0.0, 1.0) * (snr_db / CONFIDENCE_FULL_SNR_DB).clamp(0.0, 1.0)
+}
+
+/// 3点の放物線補間で極値位置のずれ（-0.5〜0.5）を算出
+pub fn parabolic_offset(y0: f32, y1: f32, y2: f32) -> f32 {
+    let denom = y0 - 2.0 * y1 + y2;
+    if denom.abs() > 1e-12 {
+        (0.5 * (y0 - y2) / denom).clamp(-0.5, 0.5)
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::SHORT_FFT_SIZE;
+    use crate::dsp::test_signals::{harmonic_tone, Lcg};
+
+    fn tone(freq: f32, sample_rate: usize, len: usize) -> Vec<f32> {
+        harmonic_tone(
+            freq as f64,
+            &[1.0, 1.0 / 2.0, 1.0 / 3.0, 1.0 / 4.0],
+            0.0,
+            1.0,
+            sample_rate,
+            len,
+        )
+    }
+
+    #[test]
+    fn test_fft_detector_uses_input_length_as_window() {
+        // 同じ検出器で長い窓と短い窓を交互に使っても、それぞれの解析器が再利用され、どちらもサブセント精度で推定できる
+        let mut detector = FftPeakDetector::new();
+        let range = SearchRange::unrestricted(75.0, 1000.0);
+        let samples = tone(329.63, 48000, FFT_SIZE);
+        for _ in 0..2 {
+            for frame_size in [FFT_SIZE, SHORT_FFT_SIZE] {
+                let estimate = detector
+                    .detect(&samples[FFT_SIZE - frame_size..], 48000, &range)
+                    .unwrap();
+                let cents = 1200.0 * (estimate.fundamental.unwrap() / 329.63).log2();
+                assert!(cents.abs() < 0.1, "{}: cents = {}", frame_size, cents);
+            }
+        }
+        assert_eq!(detector.analyzers.len(), 2);
+    }
+
+    #[test]
+    fn test_spectral_confidence() {
+        assert_eq!(spectral_confidence(1.0, 100.0), 1.0);
+        assert!((spectral_confidence(1.0, 10.0) - 0.5).abs() < 1e-6);
+        assert!((spectral_confidence(0.6, 1000.0) - 0.6).abs() < 1e-6);
+        assert_eq!(spectral_confidence(1.0, 0.5), 0.0);
+
+        // はっきりした音はノイズに埋もれかけた音より信頼度が高い
+        let mut detector = FftPeakDetector::new();
+        let range = SearchRange::unrestricted(75.0, 1000.0);
+        let clean = tone(110.0, 48000, FFT_SIZE);
+        let mut noisy: Vec<f32> = clean.iter().map(|&x| x * 0.05).collect();
+        Lcg::new(0x2468_ace0).add_noise(&mut noisy, 0.5);
+        let clean = detector.detect(&clean, 48000, &range).unwrap();
+        assert_eq!(clean.fft_size, Some(FFT_SIZE * 2));
+        assert!(clean.confidence > 0.8, "clean: {:?}", clean);
+        let noisy = detector.detect(&noisy, 48000, &range).unwrap();
+        assert!(noisy.confidence < clean.confidence, "noisy: {:?}", noisy);
+        assert!(noisy.peak_to_noise < clean.peak_to_noise);
+    }
+}
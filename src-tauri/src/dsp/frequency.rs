@@ -1,62 +1,226 @@
-use crate::constants::{GUITAR_FREQUENCIES, GUITAR_TOLERANCE, MAX_FREQUENCY, MIN_FREQUENCY};
+use realfft::num_complex::Complex;
 
-/// 検出されたピーク周波数から基音候補を探し、ギター音にマッチするものを返す
-pub fn detect_guitar_fundamental(
-    freq: f32,
-    spectrum: &[f32],
+use crate::constants::{HPS_HARMONICS, HPS_OCTAVE_TOLERANCE, HPS_PRESENCE_RATIO};
+use crate::dsp::detector::{parabolic_offset, SearchRange};
+use crate::dsp::spectrum::Analyzer;
+
+/// ズームFFTの1段あたりの評価点数（奇数にして中央を前段の最大点に合わせる）
+const ZOOM_POINTS: usize = 17;
+
+/// ズームFFTの段数（1段ごとに探索幅を評価点間隔まで狭める）
+const ZOOM_PASSES: usize = 3;
+
+/// 基音推定の結果
+#[derive(Debug, Clone, Copy)]
+pub struct FundamentalEstimate {
+    /// 基音の周波数（Hz）
+    pub frequency: f32,
+    /// 倍音の一致度から求めた信頼度（0.0〜1.0）
+    pub confidence: f32,
+    /// 周波数の精密化に使った倍音の次数（1 = 基音）
+    pub harmonic: usize,
+    /// 精密化に使った倍音のピークbin
+    pub peak_bin: usize,
+}
+
+/// ピークbinの補間方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeakInterpolator {
+    /// 対数振幅の放物線補間（ガウス型のメインローブを持つ窓向け）
+    Gaussian = 0,
+    /// 線形振幅の放物線補間
+    Parabolic = 1,
+    /// Quinnの第2推定（複素スペクトルを使う、矩形窓で導出）
+    Quinn2 = 2,
+    /// Jainの方法（隣接binの振幅比、矩形窓で導出）
+    Jain = 3,
+}
+
+impl PeakInterpolator {
+    /// 数値から変換（範囲外はガウシアン）
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => Self::Parabolic,
+            2 => Self::Quinn2,
+            3 => Self::Jain,
+            _ => Self::Gaussian,
+        }
+    }
+
+    /// 設定ファイル用の名前から変換
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "gaussian" => Some(Self::Gaussian),
+            "parabolic" => Some(Self::Parabolic),
+            "quinn2" => Some(Self::Quinn2),
+            "jain" => Some(Self::Jain),
+            _ => None,
+        }
+    }
+
+    /// 設定ファイル用の名前
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Gaussian => "gaussian",
+            Self::Parabolic => "parabolic",
+            Self::Quinn2 => "quinn2",
+            Self::Jain => "jain",
+        }
+    }
+}
+
+/// ピーク周波数の精密化方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeakRefinement {
+    /// 振幅スペクトルのbin補間のみ
+    Interpolation = 0,
+    /// 補間結果の周辺だけを細かい周波数グリッドで評価し直す（ズームFFT）
+    Zoom = 1,
+}
+
+impl PeakRefinement {
+    /// 数値から変換（範囲外は補間のみ）
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => Self::Zoom,
+            _ => Self::Interpolation,
+        }
+    }
+
+    /// 設定ファイル用の名前から変換
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "interpolation" => Some(Self::Interpolation),
+            "zoom" => Some(Self::Zoom),
+            _ => None,
+        }
+    }
+
+    /// 設定ファイル用の名前
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Interpolation => "interpolation",
+            Self::Zoom => "zoom",
+        }
+    }
+}
+
+/// 指定bin付近（±radius）の最大値とそのbinを取得
+fn local_peak(magnitudes: &[f32], center: usize, radius: usize) -> Option<(usize, f32)> {
+    let start = center.saturating_sub(radius);
+    let end = (center + radius + 1).min(magnitudes.len());
+    (start..end)
+        .map(|i| (i, magnitudes[i]))
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+}
+
+/// 隣接binをたどって極大のbinまで移動する（候補binが主ローブの肩にある場合に頂点へ寄せる）
+fn climb_to_peak(magnitudes: &[f32], mut bin: usize) -> usize {
+    loop {
+        if bin > 0 && magnitudes[bin - 1] > magnitudes[bin] {
+            bin -= 1;
+        } else if bin + 1 < magnitudes.len() && magnitudes[bin + 1] > magnitudes[bin] {
+            bin += 1;
+        } else {
+            return bin;
+        }
+    }
+}
+
+/// 基音候補binの倍音積スコア（対数領域での倍音振幅の総和）
+/// ノイズフロア未満の倍音は一律ノイズフロアとして扱う
+fn harmonic_score(magnitudes: &[f32], bin: usize, harmonics: usize, floor: f32) -> f32 {
+    (1..=harmonics)
+        .map(|h| {
+            // 高次倍音ほどbinの量子化誤差が大きくなるので探索幅を広げる
+            let value = local_peak(magnitudes, bin * h, h / 2)
+                .map(|(_, v)| v)
+                .unwrap_or(0.0);
+            value.max(floor).ln()
+        })
+        .sum()
+}
+
+/// 倍音積スペクトル（HPS）で基音を推定
+/// magnitudes: 0 binからの振幅スペクトル（min_bin..max_binの倍音まで含むこと）
+/// range: 候補を限定する探索条件（目標周波数が空なら解析範囲内のすべてが候補）
+pub fn detect_fundamental(
+    magnitudes: &[f32],
     min_bin: usize,
     max_bin: usize,
-    padded_size: usize,
     sample_rate: usize,
+    padded_size: usize,
     noise_floor: f32,
-    max_val: f32,
-) -> Option<f32> {
-    // 基音候補を探す: 検出周波数の1/2, 1/3, 1/4をチェックして最も低いギター音を採用
-    let mut candidates: Vec<(f32, f32)> = Vec::new(); // (freq, power)
-
-    // 検出された周波数自体も候補に
-    candidates.push((freq, max_val));
-
-    // 1/2, 1/3, 1/4（倍音→基音）をチェック
-    for divisor in [2.0f32, 3.0, 4.0] {
-        let sub_freq = freq / divisor;
-        let sub_freq_bin = (sub_freq * padded_size as f32 / sample_rate as f32) as usize;
-        if sub_freq_bin >= min_bin && sub_freq_bin < max_bin {
-            let sub_idx = sub_freq_bin - min_bin;
-            if sub_idx < spectrum.len() {
-                let sub_val = spectrum[sub_idx];
-                // ノイズフロアの1.5倍以上のピークがあれば候補に追加
-                if sub_val > noise_floor * 1.5 {
-                    candidates.push((sub_freq, sub_val));
-                }
-            }
-        }
+    range: &SearchRange,
+) -> Option<FundamentalEstimate> {
+    let max_bin = max_bin.min(magnitudes.len());
+    if min_bin == 0 || min_bin >= max_bin {
+        return None;
     }
 
-    // 周波数の低い順にソート
-    candidates.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    // 目標周波数の許容範囲内にある候補binのみ評価する
+    let bin_hz = sample_rate as f32 / padded_size as f32;
+    let is_candidate = |bin: usize| range.matches(bin as f32 * bin_hz);
 
-    // 候補の中からギター音にマッチするものを探す（低い周波数優先）
-    for (candidate_freq, candidate_power) in &candidates {
-        for &target in &GUITAR_FREQUENCIES {
-            let ratio = candidate_freq / target;
-            if ratio > (1.0 - GUITAR_TOLERANCE) && ratio < (1.0 + GUITAR_TOLERANCE) {
-                // パワーが十分あるか確認（メインピークの10%以上）
-                if *candidate_power > max_val * 0.1 {
-                    return Some(*candidate_freq);
-                }
-            }
+    // 基音そのものがノイズフロアを上回る候補をN倍音でスコアリング
+    // （倍音の少ない高音で、1オクターブ下の候補と同点になるのを防ぐ）
+    let floor = noise_floor.max(1e-10);
+    let (mut best_bin, best_score) = (min_bin..max_bin)
+        .filter(|&bin| is_candidate(bin) && magnitudes[bin] > floor * HPS_PRESENCE_RATIO)
+        .map(|bin| (bin, harmonic_score(magnitudes, bin, HPS_HARMONICS, floor)))
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())?;
+
+    // オクターブ誤り対策: 1オクターブ下の候補に基音が実在し、スコアが十分近ければそちらを採用
+    let sub_bin = best_bin / 2;
+    if sub_bin >= min_bin
+        && is_candidate(sub_bin)
+        && magnitudes[sub_bin] > floor * HPS_PRESENCE_RATIO
+    {
+        let sub_score = harmonic_score(magnitudes, sub_bin, HPS_HARMONICS, floor);
+        if (best_score - sub_score) / HPS_HARMONICS as f32 <= HPS_OCTAVE_TOLERANCE {
+            best_bin = sub_bin;
         }
     }
 
-    None
+    // 最も強い倍音のピークを補間し、倍音次数で割って基音を精密化
+    // （候補binはノイズの影響で主ローブの頂点から外れうるので、補間の前に極大へ寄せる）
+    let (harmonic, peak_bin, _) = (1..=HPS_HARMONICS)
+        .filter_map(|h| {
+            local_peak(magnitudes, best_bin * h, h / 2).map(|(i, _)| {
+                let peak = climb_to_peak(magnitudes, i);
+                (h, peak, magnitudes[peak])
+            })
+        })
+        .max_by(|a, b| a.2.partial_cmp(&b.2).unwrap())?;
+    let frequency =
+        gaussian_interpolation(magnitudes, peak_bin, 0, sample_rate, padded_size) / harmonic as f32;
+
+    // 信頼度: ノイズフロアを十分上回る倍音の割合（低次倍音ほど重み大）
+    let (present, total) = (1..=HPS_HARMONICS).fold((0.0f32, 0.0f32), |(p, t), h| {
+        let weight = 1.0 / h as f32;
+        let value = local_peak(magnitudes, best_bin * h, h / 2)
+            .map(|(_, v)| v)
+            .unwrap_or(0.0);
+        if value > noise_floor * HPS_PRESENCE_RATIO {
+            (p + weight, t + weight)
+        } else {
+            (p, t + weight)
+        }
+    });
+
+    Some(FundamentalEstimate {
+        frequency,
+        confidence: present / total,
+        harmonic,
+        peak_bin,
+    })
 }
 
-/// 周波数がギター音の範囲内かどうかを判定
-pub fn is_guitar_frequency(freq: f32) -> bool {
-    GUITAR_FREQUENCIES.iter().any(|&target| {
+/// 周波数がいずれかの目標周波数（弦）の許容範囲内かどうかを判定
+pub fn is_target_frequency(freq: f32, targets: &[f32], tolerance: f32) -> bool {
+    targets.iter().any(|&target| {
         let ratio = freq / target;
-        ratio > (1.0 - GUITAR_TOLERANCE) && ratio < (1.0 + GUITAR_TOLERANCE)
+        ratio > (1.0 - tolerance) && ratio < (1.0 + tolerance)
     })
 }
 
@@ -91,19 +255,197 @@ pub fn gaussian_interpolation(
     }
 }
 
+/// ピークbinとその両隣の複素スペクトルから、binの小数部のずれ（-0.5〜0.5）を推定
+pub fn interpolate_peak_offset(
+    interpolator: PeakInterpolator,
+    prev: Complex<f32>,
+    peak: Complex<f32>,
+    next: Complex<f32>,
+) -> f32 {
+    let (y0, y1, y2) = (prev.norm(), peak.norm(), next.norm());
+    let delta = match interpolator {
+        PeakInterpolator::Gaussian => {
+            let ln = |v: f32| (v + 1e-10).ln();
+            parabolic_offset(ln(y0), ln(y1), ln(y2))
+        }
+        PeakInterpolator::Parabolic => parabolic_offset(y0, y1, y2),
+        PeakInterpolator::Quinn2 => quinn2_offset(prev, peak, next),
+        PeakInterpolator::Jain if y1 <= 0.0 => 0.0,
+        PeakInterpolator::Jain => {
+            // 大きい方の隣接binに向かって、振幅比 a から a / (1 + a) だけずらす
+            if y2 > y0 {
+                let a = y2 / y1;
+                a / (1.0 + a)
+            } else {
+                let a = y0 / y1;
+                -a / (1.0 + a)
+            }
+        }
+    };
+    delta.clamp(-0.5, 0.5)
+}
+
+/// Quinnの第2推定（X[k-1], X[k], X[k+1] の複素比から）
+fn quinn2_offset(prev: Complex<f32>, peak: Complex<f32>, next: Complex<f32>) -> f32 {
+    let denom = peak.norm_sqr() as f64;
+    if denom <= 0.0 {
+        return 0.0;
+    }
+    let ap = (next * peak.conj()).re as f64 / denom;
+    let am = (prev * peak.conj()).re as f64 / denom;
+    let dp = -ap / (1.0 - ap);
+    let dm = am / (1.0 - am);
+    let tau = |x: f64| {
+        let r = (2.0f64 / 3.0).sqrt();
+        0.25 * (3.0 * x * x + 6.0 * x + 1.0).ln()
+            - 6.0f64.sqrt() / 24.0 * ((x + 1.0 - r) / (x + 1.0 + r)).ln()
+    };
+    let delta = (dp + dm) / 2.0 + tau(dp * dp) - tau(dm * dm);
+    if delta.is_finite() {
+        delta as f32
+    } else {
+        0.0
+    }
+}
+
+/// 窓をかけていない信号の、任意のbin位置（フレーム長基準）のDFT
+fn dft_bin(samples: &[f32], bin: f64) -> Complex<f32> {
+    let omega = -2.0 * std::f64::consts::PI * bin / samples.len() as f64;
+    let step = Complex::new(omega.cos(), omega.sin());
+    let mut phasor = Complex::new(1.0f64, 0.0);
+    let mut sum = Complex::new(0.0f64, 0.0);
+    for &x in samples {
+        sum += phasor * x as f64;
+        phasor *= step;
+    }
+    Complex::new(sum.re as f32, sum.im as f32)
+}
+
+/// ピーク周波数の推定方法（bin補間 + 精密化）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeakEstimator {
+    pub interpolator: PeakInterpolator,
+    pub refinement: PeakRefinement,
+}
+
+impl PeakEstimator {
+    /// 解析器が直前に求めたスペクトルから、peak_binのピーク周波数（Hz）を求める
+    pub fn frequency(self, analyzer: &Analyzer, peak_bin: usize, sample_rate: usize) -> f32 {
+        let bin_hz = sample_rate as f32 / analyzer.padded_size() as f32;
+        let coarse = match self.interpolator {
+            PeakInterpolator::Gaussian | PeakInterpolator::Parabolic => {
+                let spectrum = analyzer.spectrum();
+                if peak_bin == 0 || peak_bin + 1 >= spectrum.len() {
+                    peak_bin as f32 * bin_hz
+                } else {
+                    let offset = interpolate_peak_offset(
+                        self.interpolator,
+                        spectrum[peak_bin - 1],
+                        spectrum[peak_bin],
+                        spectrum[peak_bin + 1],
+                    );
+                    (peak_bin as f32 + offset) * bin_hz
+                }
+            }
+            // 矩形窓・ゼロパディングなしのDFTで導出された推定なので、
+            // 窓をかける前の信号からフレーム長基準の隣接binを直接計算する
+            PeakInterpolator::Quinn2 | PeakInterpolator::Jain => {
+                let samples = analyzer.samples();
+                let frame_bin_hz = sample_rate as f32 / samples.len() as f32;
+                let center = (peak_bin as f32 * bin_hz / frame_bin_hz).round() as f64;
+                let values: [Complex<f32>; 5] =
+                    std::array::from_fn(|i| dft_bin(samples, center + i as f64 - 2.0));
+                // 丸めで隣のbinを中心にした場合は、最大のbinを中心に取り直す
+                let best = (1..4)
+                    .max_by(|&a, &b| values[a].norm().partial_cmp(&values[b].norm()).unwrap())
+                    .unwrap();
+                let offset = interpolate_peak_offset(
+                    self.interpolator,
+                    values[best - 1],
+                    values[best],
+                    values[best + 1],
+                );
+                (center as f32 + best as f32 - 2.0 + offset) * frame_bin_hz
+            }
+        };
+        match self.refinement {
+            PeakRefinement::Interpolation => coarse,
+            PeakRefinement::Zoom => zoom_refine(analyzer.frame(), sample_rate, coarse, bin_hz),
+        }
+    }
+}
+
+/// 粗い推定値の周辺（±span_hz）だけでスペクトルを細かく評価し、ピーク周波数を精密化する
+/// 評価点ごとにGoertzelアルゴリズムでDTFTの振幅を求めるので、FFT_SIZEを大きくせずにセント以下の分解能が得られる
+/// frame: 窓かけ済みの信号
+pub fn zoom_refine(frame: &[f32], sample_rate: usize, coarse_freq: f32, span_hz: f32) -> f32 {
+    if frame.is_empty() || coarse_freq <= 0.0 || span_hz <= 0.0 {
+        return coarse_freq;
+    }
+    let half = (ZOOM_POINTS / 2) as f64;
+    let mut center = coarse_freq as f64;
+    let mut step = span_hz as f64 / half;
+    let mut powers = [0.0f64; ZOOM_POINTS];
+
+    for pass in 0..ZOOM_PASSES {
+        for (i, power) in powers.iter_mut().enumerate() {
+            let freq = center + (i as f64 - half) * step;
+            *power = goertzel_power(frame, sample_rate, freq);
+        }
+        let (best, _) = powers
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+            .unwrap();
+
+        // 最後の段は最大点の両隣との放物線補間（対数振幅）で仕上げる
+        let offset = if pass + 1 == ZOOM_PASSES && best > 0 && best < ZOOM_POINTS - 1 {
+            let ln = |p: f64| (p + 1e-30).ln() as f32;
+            parabolic_offset(ln(powers[best - 1]), ln(powers[best]), ln(powers[best + 1])) as f64
+        } else {
+            0.0
+        };
+        center += (best as f64 - half + offset) * step;
+        step /= half;
+    }
+    center as f32
+}
+
+/// Goertzelアルゴリズムで任意の周波数（binの整数倍でなくてよい）のパワーを計算
+fn goertzel_power(frame: &[f32], sample_rate: usize, freq: f64) -> f64 {
+    let omega = 2.0 * std::f64::consts::PI * freq / sample_rate as f64;
+    let coeff = 2.0 * omega.cos();
+    let (mut s1, mut s2) = (0.0f64, 0.0f64);
+    for &x in frame {
+        let s0 = x as f64 + coeff * s1 - s2;
+        s2 = s1;
+        s1 = s0;
+    }
+    s1 * s1 + s2 * s2 - coeff * s1 * s2
+}
+
 /// スペクトルの中央値からノイズフロアを計算
-pub fn calculate_noise_floor(spectrum: &[f32]) -> f32 {
-    let mut sorted_spectrum = spectrum.to_vec();
-    sorted_spectrum.sort_by(|a, b| a.partial_cmp(b).unwrap());
-    sorted_spectrum[sorted_spectrum.len() / 2]
+/// scratch: 並べ替え用の作業バッファ（フレームごとにアロケーションしないよう呼び出し側で再利用する）
+pub fn calculate_noise_floor(spectrum: &[f32], scratch: &mut Vec<f32>) -> f32 {
+    scratch.clear();
+    scratch.extend_from_slice(spectrum);
+    let mid = scratch.len() / 2;
+    *scratch
+        .select_nth_unstable_by(mid, |a, b| a.partial_cmp(b).unwrap())
+        .1
 }
 
 /// 周波数解析範囲のbin番号を計算
 /// 戻り値: (min_bin, max_bin) - 常に min_bin < max_bin を保証
-pub fn calculate_frequency_bins(sample_rate: usize, padded_size: usize) -> (usize, usize) {
-    let min_bin = (MIN_FREQUENCY * padded_size as f32 / sample_rate as f32) as usize;
+pub fn calculate_frequency_bins(
+    sample_rate: usize,
+    padded_size: usize,
+    min_freq: f32,
+    max_freq: f32,
+) -> (usize, usize) {
+    let min_bin = (min_freq * padded_size as f32 / sample_rate as f32) as usize;
     let max_bin = std::cmp::min(
-        (MAX_FREQUENCY * padded_size as f32 / sample_rate as f32) as usize,
+        (max_freq * padded_size as f32 / sample_rate as f32) as usize,
         padded_size / 2,
     );
     // min_bin < max_bin を保証
@@ -117,21 +459,192 @@ pub fn calculate_frequency_bins(sample_rate: usize, padded_size: usize) -> (usiz
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::dsp::window::WindowKind;
+    use crate::tuning::standard_tuning;
 
     #[test]
-    fn test_is_guitar_frequency() {
+    fn test_is_target_frequency() {
+        let targets = standard_tuning().frequencies();
+
         // E2 (82.41Hz) の範囲内
-        assert!(is_guitar_frequency(82.0));
-        assert!(is_guitar_frequency(85.0));
+        assert!(is_target_frequency(82.0, &targets, 0.15));
+        assert!(is_target_frequency(85.0, &targets, 0.15));
 
         // 範囲外
-        assert!(!is_guitar_frequency(50.0));
-        assert!(!is_guitar_frequency(500.0));
+        assert!(!is_target_frequency(50.0, &targets, 0.15));
+        assert!(!is_target_frequency(500.0, &targets, 0.15));
+    }
+
+    #[test]
+    fn test_detect_fundamental_limited_to_targets() {
+        // E2 (bin 56) と その第2倍音 (bin 112 ≒ 164Hz): 目標がB3/E4のみなら候補外
+        let spectrum = harmonic_spectrum(56, &[1.0, 0.8], 2000);
+        let range = SearchRange::for_targets(vec![246.94, 329.63], 75.0, 350.0, 0.15);
+        assert!(detect_fundamental(&spectrum, 51, 239, 48000, 32768, 0.01, &range).is_none());
+    }
+
+    /// 指定した倍音振幅を持つ理想的なスペクトル（bin単位の線スペクトル）を作成
+    fn harmonic_spectrum(fundamental_bin: usize, amplitudes: &[f32], len: usize) -> Vec<f32> {
+        let mut spectrum = vec![0.01; len];
+        for (h, &amp) in amplitudes.iter().enumerate() {
+            let bin = fundamental_bin * (h + 1);
+            if bin < len {
+                spectrum[bin] = amp;
+            }
+        }
+        spectrum
+    }
+
+    #[test]
+    fn test_detect_fundamental_with_strong_second_harmonic() {
+        // 32768点FFT @48kHz: E2 (82.41Hz) ≒ bin 56
+        let spectrum = harmonic_spectrum(56, &[0.3, 1.0, 0.6, 0.4, 0.3], 2000);
+        let range = SearchRange::unrestricted(75.0, 350.0);
+        let result = detect_fundamental(&spectrum, 51, 239, 48000, 32768, 0.01, &range).unwrap();
+        let bin = result.frequency * 32768.0 / 48000.0;
+        assert!((bin - 56.0).abs() < 0.5, "bin = {}", bin);
+        assert!(result.confidence > 0.9);
+    }
+
+    #[test]
+    fn test_detect_fundamental_rejects_sub_octave() {
+        // G3 (196Hz) ≒ bin 134: 1オクターブ下（bin 67）を選ばない
+        let spectrum = harmonic_spectrum(134, &[1.0, 0.5, 0.3, 0.2, 0.1], 2000);
+        let range = SearchRange::unrestricted(75.0, 350.0);
+        let result = detect_fundamental(&spectrum, 51, 239, 48000, 32768, 0.01, &range).unwrap();
+        let bin = result.frequency * 32768.0 / 48000.0;
+        assert!((bin - 134.0).abs() < 0.5, "bin = {}", bin);
+    }
+
+    #[test]
+    fn test_detect_fundamental_interpolates_from_lobe_peak() {
+        // 主ローブの肩（bin 57）が倍音積で選ばれても、補間は頂点（bin 56）から行う
+        let mut spectrum = harmonic_spectrum(56, &[1.0], 2000);
+        spectrum[55] = 0.6;
+        spectrum[57] = 0.9;
+        spectrum[113] = 0.8;
+        spectrum[169] = 0.5;
+        spectrum[170] = 0.6;
+        let range = SearchRange::unrestricted(75.0, 350.0);
+        let result = detect_fundamental(&spectrum, 51, 239, 48000, 32768, 0.01, &range).unwrap();
+        assert_eq!((result.harmonic, result.peak_bin), (1, 56));
+        let bin = result.frequency * 32768.0 / 48000.0;
+        assert!((bin - 56.33).abs() < 0.05, "bin = {}", bin);
     }
 
     #[test]
     fn test_calculate_noise_floor() {
         let spectrum = vec![1.0, 2.0, 3.0, 4.0, 5.0];
-        assert_eq!(calculate_noise_floor(&spectrum), 3.0);
+        assert_eq!(calculate_noise_floor(&spectrum, &mut Vec::new()), 3.0);
+    }
+
+    #[test]
+    fn test_zoom_refine_resolves_sub_cent() {
+        // bin補間では誤差が残る周波数でも、ズームFFTで0.05セント以内に収まる
+        let (sample_rate, len) = (48000, 16384);
+        let window = WindowKind::BlackmanHarris.table(len);
+        for freq in [82.41f64, 110.37, 196.02, 329.63] {
+            let frame: Vec<f32> = (0..len)
+                .map(|i| {
+                    let t = i as f64 / sample_rate as f64;
+                    let x = (2.0 * std::f64::consts::PI * freq * t + 0.4).sin();
+                    x as f32 * window[i]
+                })
+                .collect();
+            let bin_hz = sample_rate as f32 / (len * 2) as f32;
+            let coarse = freq as f32 + 0.4 * bin_hz;
+            let refined = zoom_refine(&frame, sample_rate, coarse, bin_hz);
+            let cents = 1200.0 * (refined as f64 / freq).log2();
+            assert!(cents.abs() < 0.05, "{} Hz: cents = {}", freq, cents);
+        }
+    }
+
+    #[test]
+    fn test_peak_refinement_name_round_trip() {
+        for refinement in [PeakRefinement::Interpolation, PeakRefinement::Zoom] {
+            assert_eq!(
+                PeakRefinement::from_name(refinement.name()),
+                Some(refinement)
+            );
+            assert_eq!(PeakRefinement::from_u32(refinement as u32), refinement);
+        }
+    }
+
+    /// 窓関数と補間方法の組み合わせで、bin間を0.05刻みで動かした正弦波の最大誤差（パディング後のbin単位）
+    fn worst_interpolation_error(window: WindowKind, interpolator: PeakInterpolator) -> f64 {
+        let (sample_rate, frame_size) = (48000, 4096);
+        let mut analyzer = Analyzer::new(frame_size, 2, window);
+        let bin_hz = sample_rate as f64 / analyzer.padded_size() as f64;
+        let estimator = PeakEstimator {
+            interpolator,
+            refinement: PeakRefinement::Interpolation,
+        };
+        (0..20)
+            .map(|k| {
+                let freq = (100.0 + k as f64 * 0.05) * bin_hz;
+                let samples: Vec<f32> = (0..frame_size)
+                    .map(|i| {
+                        let t = i as f64 / sample_rate as f64;
+                        (2.0 * std::f64::consts::PI * freq * t + 0.3).sin() as f32
+                    })
+                    .collect();
+                let magnitudes = analyzer.process(&samples);
+                let (peak_bin, _) = magnitudes
+                    .iter()
+                    .enumerate()
+                    .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+                    .unwrap();
+                let estimate = estimator.frequency(&analyzer, peak_bin, sample_rate);
+                ((estimate as f64 - freq) / bin_hz).abs()
+            })
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn test_interpolator_accuracy() {
+        // Blackman-Harris窓 + 2倍のゼロパディング（デフォルトの解析条件）
+        for (interpolator, limit) in [
+            (PeakInterpolator::Gaussian, 0.002),
+            (PeakInterpolator::Parabolic, 0.02),
+            (PeakInterpolator::Quinn2, 0.01),
+            (PeakInterpolator::Jain, 0.01),
+        ] {
+            let error = worst_interpolation_error(WindowKind::BlackmanHarris, interpolator);
+            assert!(error < limit, "{}: {} bins", interpolator.name(), error);
+        }
+    }
+
+    #[test]
+    fn test_window_accuracy_with_gaussian_interpolation() {
+        // フラットトップ窓はメインローブが平らなので周波数の偏りが大きい（振幅測定向け）
+        for (window, limit) in [
+            (WindowKind::Hann, 0.005),
+            (WindowKind::Hamming, 0.005),
+            (WindowKind::BlackmanHarris, 0.002),
+            (WindowKind::Kaiser(8.6), 0.002),
+            (WindowKind::FlatTop, 0.3),
+        ] {
+            let error = worst_interpolation_error(window, PeakInterpolator::Gaussian);
+            assert!(error < limit, "{}: {} bins", window.name(), error);
+        }
+    }
+
+    #[test]
+    fn test_peak_interpolator_name_round_trip() {
+        for interpolator in [
+            PeakInterpolator::Gaussian,
+            PeakInterpolator::Parabolic,
+            PeakInterpolator::Quinn2,
+            PeakInterpolator::Jain,
+        ] {
+            assert_eq!(
+                PeakInterpolator::from_name(interpolator.name()),
+                Some(interpolator)
+            );
+            assert_eq!(
+                PeakInterpolator::from_u32(interpolator as u32),
+                interpolator
+            );
+        }
     }
 }
@@ -0,0 +1,178 @@
+use std::f32::consts::PI;
+
+use crate::dsp::detector::{PitchDetector, PitchEstimate, SearchRange};
+use crate::dsp::yin::{estimate_yin, YinBuffers};
+
+/// 間引き後の目標サンプルレート（Hz、ベースの倍音を十分に含む）
+const DECIMATED_RATE: usize = 8000;
+
+/// 間引き係数あたりのローパスFIRのタップ数
+const TAPS_PER_FACTOR: usize = 16;
+
+/// ローパスのカットオフ（間引き後のサンプルレートに対する比率）
+const CUTOFF_RATIO: f32 = 0.4;
+
+/// 低音域（ベース）用の検出器
+/// 入力を間引いてからバッファ全体を積分窓としてYINを適用する。
+/// FFTのbin幅では数十セントの誤差が出るB0〜E1でも、周期を時間領域で直接測るので±1セントに収まる
+pub struct LowFrequencyDetector {
+    /// 現在のフィルタを設計したサンプルレート
+    sample_rate: usize,
+    /// 間引き係数
+    factor: usize,
+    /// アンチエイリアス用ローパスFIR係数
+    filter: Vec<f32>,
+    /// 間引き後の信号（再利用）
+    decimated: Vec<f32>,
+    /// YINの作業領域（再利用）
+    yin: YinBuffers,
+}
+
+impl LowFrequencyDetector {
+    pub fn new() -> Self {
+        Self {
+            sample_rate: 0,
+            factor: 1,
+            filter: Vec::new(),
+            decimated: Vec::new(),
+            yin: YinBuffers::default(),
+        }
+    }
+
+    /// サンプルレートに合わせて間引き係数とフィルタを準備
+    fn prepare(&mut self, sample_rate: usize) {
+        if self.sample_rate == sample_rate {
+            return;
+        }
+        self.sample_rate = sample_rate;
+        self.factor = (sample_rate / DECIMATED_RATE).max(1);
+        self.filter = if self.factor > 1 {
+            design_lowpass(
+                TAPS_PER_FACTOR * self.factor + 1,
+                CUTOFF_RATIO / self.factor as f32,
+            )
+        } else {
+            Vec::new()
+        };
+    }
+
+    /// ローパスをかけながら間引く（出力点のみ畳み込みを計算）
+    fn decimate(&mut self, samples: &[f32]) {
+        self.decimated.clear();
+        if self.factor == 1 {
+            self.decimated.extend_from_slice(samples);
+            return;
+        }
+        let taps = self.filter.len();
+        self.decimated
+            .extend((taps - 1..samples.len()).step_by(self.factor).map(|n| {
+                self.filter
+                    .iter()
+                    .zip(samples[n + 1 - taps..=n].iter().rev())
+                    .map(|(h, x)| h * x)
+                    .sum::<f32>()
+            }));
+    }
+}
+
+impl PitchDetector for LowFrequencyDetector {
+    fn detect(
+        &mut self,
+        samples: &[f32],
+        sample_rate: usize,
+        range: &SearchRange,
+    ) -> Option<PitchEstimate> {
+        self.prepare(sample_rate);
+        self.decimate(samples);
+        let decimated_rate = sample_rate as f32 / self.factor as f32;
+
+        // 間引き後のレートでYIN（周波数はサンプルレートの比で元に戻す）
+        let (freq, clarity) = estimate_yin(
+            &self.decimated,
+            decimated_rate.round() as usize,
+            range.min_freq,
+            range.max_freq,
+            &mut self.yin,
+        )?;
+        let freq = freq * decimated_rate / decimated_rate.round();
+
+        Some(PitchEstimate {
+            raw_frequency: freq,
+            fundamental: Some(freq),
+            confidence: clarity,
+            peak_to_noise: None,
+            fft_size: None,
+        })
+    }
+}
+
+/// Blackman窓の窓関数法でローパスFIRを設計（DCゲイン1に正規化）
+/// cutoff: 入力サンプルレートに対するカットオフ周波数の比率
+fn design_lowpass(taps: usize, cutoff: f32) -> Vec<f32> {
+    let center = (taps - 1) as f32 / 2.0;
+    let mut filter: Vec<f32> = (0..taps)
+        .map(|i| {
+            let x = i as f32 - center;
+            let sinc = if x == 0.0 {
+                2.0 * cutoff
+            } else {
+                (2.0 * PI * cutoff * x).sin() / (PI * x)
+            };
+            let phase = 2.0 * PI * i as f32 / (taps - 1) as f32;
+            let window = 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos();
+            sinc * window
+        })
+        .collect();
+    let gain: f32 = filter.iter().sum();
+    filter.iter_mut().for_each(|h| *h /= gain);
+    filter
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::test_signals::{harmonic_tone, Lcg};
+
+    fn assert_within_one_cent(freq: f32, amplitudes: &[f64], sample_rate: usize) {
+        // 倍音位相はずらし、小さなノイズを加える
+        let mut samples = harmonic_tone(freq as f64, amplitudes, 0.0, 0.7, sample_rate, 16384);
+        Lcg::new(0x2545_f491).add_noise(&mut samples, 0.02);
+        let range = SearchRange::unrestricted(28.0, 200.0);
+        let estimate = LowFrequencyDetector::new()
+            .detect(&samples, sample_rate, &range)
+            .unwrap();
+        let cents = 1200.0 * (estimate.fundamental.unwrap() / freq).log2();
+        assert!(
+            cents.abs() < 1.0,
+            "{} Hz @ {} Hz: cents = {}",
+            freq,
+            sample_rate,
+            cents
+        );
+    }
+
+    #[test]
+    fn test_b0_within_one_cent() {
+        // B0 = 30.87Hz: 基音が弱く倍音の強いベースを想定
+        for sample_rate in [44100, 48000, 96000] {
+            assert_within_one_cent(30.868, &[1.0, 0.8, 0.5, 0.3], sample_rate);
+            assert_within_one_cent(30.868, &[0.3, 1.0, 0.6, 0.4, 0.2], sample_rate);
+        }
+    }
+
+    #[test]
+    fn test_e1_within_one_cent() {
+        // E1 = 41.20Hz
+        for sample_rate in [44100, 48000, 96000] {
+            assert_within_one_cent(41.203, &[1.0, 0.8, 0.5, 0.3], sample_rate);
+            assert_within_one_cent(41.203, &[0.3, 1.0, 0.6, 0.4, 0.2], sample_rate);
+        }
+    }
+
+    #[test]
+    fn test_lowpass_unity_dc_gain() {
+        let filter = design_lowpass(97, 0.4 / 6.0);
+        let gain: f32 = filter.iter().sum();
+        assert!((gain - 1.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,159 @@
+use crate::dsp::detector::{parabolic_offset, PitchDetector, PitchEstimate, SearchRange};
+
+/// キー最大値の採用基準（最大のピークに対する比率）
+const MCLEOD_CUTOFF: f32 = 0.93;
+
+/// 有効な音とみなす最小の明瞭度
+const MCLEOD_MIN_CLARITY: f32 = 0.5;
+
+/// 解析窓の最大長（サンプル数）
+const MCLEOD_MAX_WINDOW: usize = 4096;
+
+/// 時間領域のMcLeod Pitch Method（NSDF）による検出器
+pub struct McLeodDetector {
+    buffers: McLeodBuffers,
+}
+
+impl McLeodDetector {
+    pub fn new() -> Self {
+        Self {
+            buffers: McLeodBuffers::default(),
+        }
+    }
+}
+
+/// NSDFとキー最大値の作業領域（フレームごとに確保しないよう検出器が保持して再利用する）
+#[derive(Default)]
+pub struct McLeodBuffers {
+    nsdf: Vec<f32>,
+    key_maxima: Vec<usize>,
+}
+
+impl PitchDetector for McLeodDetector {
+    fn detect(
+        &mut self,
+        samples: &[f32],
+        sample_rate: usize,
+        range: &SearchRange,
+    ) -> Option<PitchEstimate> {
+        let (freq, clarity) = estimate_mcleod(
+            samples,
+            sample_rate,
+            range.min_freq,
+            range.max_freq,
+            &mut self.buffers,
+        )?;
+        Some(PitchEstimate {
+            raw_frequency: freq,
+            fundamental: Some(freq),
+            confidence: clarity,
+            peak_to_noise: None,
+            fft_size: None,
+        })
+    }
+}
+
+/// 正規化二乗差分関数（NSDF）を計算してresultに書き込む
+fn nsdf(x: &[f32], max_lag: usize, result: &mut Vec<f32>) {
+    let n = x.len();
+    result.clear();
+    result.resize(max_lag + 1, 0.0);
+
+    // m(tau) = Σ(x[j]^2 + x[j+tau]^2) を逐次更新
+    let mut m: f32 = 2.0 * x.iter().map(|v| v * v).sum::<f32>();
+    for (tau, value) in result.iter_mut().enumerate() {
+        if tau > 0 {
+            m -= x[tau - 1] * x[tau - 1] + x[n - tau] * x[n - tau];
+        }
+        let r: f32 = x[..n - tau].iter().zip(&x[tau..]).map(|(a, b)| a * b).sum();
+        *value = if m > 1e-12 { 2.0 * r / m } else { 0.0 };
+    }
+}
+
+/// McLeod Pitch Methodで基本周波数を推定
+/// 戻り値: (周波数, 明瞭度 0.0〜1.0) - 周期性が見つからなければNone
+pub fn estimate_mcleod(
+    samples: &[f32],
+    sample_rate: usize,
+    min_freq: f32,
+    max_freq: f32,
+    buffers: &mut McLeodBuffers,
+) -> Option<(f32, f32)> {
+    let tau_min = ((sample_rate as f32 / max_freq) as usize).max(1);
+    let tau_max = (sample_rate as f32 / min_freq).ceil() as usize;
+    let window = samples.len().min(MCLEOD_MAX_WINDOW.max(tau_max * 3));
+    if window < tau_max * 2 + 2 {
+        return None;
+    }
+    let x = &samples[samples.len() - window..];
+    nsdf(x, tau_max + 1, &mut buffers.nsdf);
+    let n = &buffers.nsdf;
+
+    // 正→負のゼロ交差以降、正の区間ごとに最大値（キー最大値）を集める
+    let key_maxima = &mut buffers.key_maxima;
+    key_maxima.clear();
+    let mut tau = 1;
+    while tau <= tau_max && n[tau] > 0.0 {
+        tau += 1;
+    }
+    while tau <= tau_max {
+        while tau <= tau_max && n[tau] <= 0.0 {
+            tau += 1;
+        }
+        let mut best: Option<usize> = None;
+        while tau <= tau_max && n[tau] > 0.0 {
+            if best.is_none_or(|b| n[tau] > n[b]) {
+                best = Some(tau);
+            }
+            tau += 1;
+        }
+        if let Some(b) = best {
+            if b >= tau_min {
+                key_maxima.push(b);
+            }
+        }
+    }
+
+    let highest = key_maxima.iter().map(|&t| n[t]).fold(0.0f32, f32::max);
+    if highest < MCLEOD_MIN_CLARITY {
+        return None;
+    }
+
+    // 最大値のcutoff倍を超える最初のキー最大値を周期とする
+    let tau = *key_maxima
+        .iter()
+        .find(|&&t| n[t] >= highest * MCLEOD_CUTOFF)?;
+    let offset = parabolic_offset(n[tau - 1], n[tau], n[tau + 1]);
+    let period = tau as f32 + offset;
+
+    Some((sample_rate as f32 / period, n[tau].clamp(0.0, 1.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::test_signals::sine;
+
+    #[test]
+    fn test_estimate_mcleod_sine() {
+        let samples = sine(196.0, 48000, 8192);
+        let (freq, clarity) =
+            estimate_mcleod(&samples, 48000, 75.0, 350.0, &mut McLeodBuffers::default()).unwrap();
+        let cents = 1200.0 * (freq / 196.0).log2();
+        assert!(cents.abs() < 1.0, "cents = {}", cents);
+        assert!(clarity > 0.9);
+    }
+
+    #[test]
+    fn test_estimate_mcleod_prefers_fundamental() {
+        // 第2倍音が基音より強いE2
+        let samples: Vec<f32> = sine(82.41, 48000, 8192)
+            .iter()
+            .zip(sine(164.82, 48000, 8192))
+            .map(|(a, b)| 0.4 * a + b)
+            .collect();
+        let (freq, _) =
+            estimate_mcleod(&samples, 48000, 75.0, 350.0, &mut McLeodBuffers::default()).unwrap();
+        assert!((freq - 82.41).abs() < 1.0, "freq = {}", freq);
+    }
+}
@@ -1,5 +1,24 @@
 mod analyzer;
+mod detector;
 mod frequency;
+mod lowfreq;
+mod mcleod;
+mod noise;
+mod onset;
+mod polyphonic;
+mod prefilter;
+mod smoothing;
+mod spectrum;
+mod strobe;
+#[cfg(test)]
+mod test_signals;
 mod window;
+mod yin;
 
-pub use analyzer::{refresh_tray_icon, run_analysis_thread};
+pub use analyzer::{get_string_targets, refresh_tray_icon, run_analysis_thread};
+pub use detector::PitchDetectorKind;
+pub use frequency::{PeakInterpolator, PeakRefinement};
+pub use noise::{NoiseProfile, NoiseReduction};
+pub use prefilter::HumFilterMode;
+pub use smoothing::PitchSmoothing;
+pub use window::WindowKind;
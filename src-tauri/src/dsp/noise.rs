@@ -0,0 +1,430 @@
+use crate::constants::{
+    CHROMATIC_MAX_FREQUENCY, CHROMATIC_MIN_FREQUENCY, FFT_SIZE, MAX_THRESHOLD_RATIO,
+    MIN_THRESHOLD_RATIO, NOISE_ADAPT_TIME_SECS, NOISE_GATE_MARGIN, NOISE_RATIO_MARGIN,
+    NOISE_SUBTRACTION_FACTOR, SPECTRAL_FLOOR,
+};
+use crate::dsp::spectrum::Analyzer;
+use crate::dsp::window::WindowKind;
+
+/// 対数を取るときの下限（無音のbinで-∞にならないようにする）
+const MIN_LEVEL: f32 = 1e-12;
+
+/// キャリブレーションで加算する解析フレーム数の下限（少ないとプロファイルがばらつき、閾値が甘くなる）
+const MIN_CALIBRATION_FRAMES: usize = 32;
+
+/// スペクトル上のノイズ除去の方法（ノイズプロファイルがあるときだけ有効）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseReduction {
+    Off = 0,
+    /// 振幅からノイズレベル（の定数倍）を差し引く
+    SpectralSubtraction = 1,
+    /// binごとのSN比から求めたWienerゲインを掛ける
+    Wiener = 2,
+}
+
+impl NoiseReduction {
+    /// 数値から変換（範囲外はオフ）
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => Self::SpectralSubtraction,
+            2 => Self::Wiener,
+            _ => Self::Off,
+        }
+    }
+
+    /// 設定ファイル用の名前から変換
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "off" => Some(Self::Off),
+            "spectral_subtraction" => Some(Self::SpectralSubtraction),
+            "wiener" => Some(Self::Wiener),
+            _ => None,
+        }
+    }
+
+    /// 設定ファイル用の名前
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::SpectralSubtraction => "spectral_subtraction",
+            Self::Wiener => "wiener",
+        }
+    }
+}
+
+/// 周波数ごとのノイズレベル
+/// 窓関数の利得で正規化しているので、窓長・窓関数の異なる解析器でも同じプロファイルを使える
+#[derive(Debug, Clone)]
+pub struct NoiseProfile {
+    /// 1binあたりの周波数（Hz）
+    bin_hz: f32,
+    /// binごとのノイズレベル（正規化した振幅の自然対数）
+    log_levels: Vec<f32>,
+    /// 入力のRMS（自然対数）
+    log_rms: f32,
+}
+
+impl NoiseProfile {
+    /// 指定した周波数のノイズレベル（正規化した振幅、Analyzer::noise_gainを掛けると振幅スペクトルの値になる）
+    pub fn level_at(&self, freq: f32) -> f32 {
+        let position = (freq / self.bin_hz).max(0.0);
+        let last = self.log_levels.len() - 1;
+        let index = (position as usize).min(last);
+        let next = (index + 1).min(last);
+        let frac = (position - index as f32).clamp(0.0, 1.0);
+        let log_level = self.log_levels[index] * (1.0 - frac) + self.log_levels[next] * frac;
+        log_level.exp()
+    }
+
+    /// ノイズのRMS
+    pub fn rms(&self) -> f32 {
+        self.log_rms.exp()
+    }
+
+    /// ノイズのRMSから決めた信号レベルのゲート
+    pub fn gate(&self) -> f32 {
+        self.rms() * NOISE_GATE_MARGIN
+    }
+
+    /// 振幅スペクトルからノイズを除去する（除去しすぎないよう元の振幅のSPECTRAL_FLOOR倍は残す）
+    /// bin_hz: スペクトルの1binの周波数、noise_gain: 解析器のAnalyzer::noise_gain
+    pub fn suppress(
+        &self,
+        magnitudes: &mut [f32],
+        bin_hz: f32,
+        noise_gain: f32,
+        reduction: NoiseReduction,
+    ) {
+        if reduction == NoiseReduction::Off {
+            return;
+        }
+        for (i, m) in magnitudes.iter_mut().enumerate() {
+            let noise = self.level_at(i as f32 * bin_hz) * noise_gain;
+            let cleaned = match reduction {
+                NoiseReduction::Off => *m,
+                NoiseReduction::SpectralSubtraction => *m - NOISE_SUBTRACTION_FACTOR * noise,
+                NoiseReduction::Wiener => {
+                    // 最尤推定のSN比 (|X|²/N² - 1) による Wiener ゲイン SNR/(1+SNR)
+                    let snr = (*m * *m / (noise * noise).max(f32::MIN_POSITIVE) - 1.0).max(0.0);
+                    *m * snr / (1.0 + snr)
+                }
+            };
+            *m = cleaned.max(*m * SPECTRAL_FLOOR);
+        }
+    }
+}
+
+/// ノイズキャリブレーションの結果
+#[derive(Debug, Clone)]
+pub struct NoiseCalibration {
+    pub profile: NoiseProfile,
+    /// 信号レベルのゲート（RMS）
+    pub gate: f32,
+    /// ピークとノイズレベルの比率の閾値
+    pub threshold_ratio: f32,
+}
+
+/// 測定中のキャリブレーション
+struct Calibration {
+    /// 残りの測定フレーム数
+    remaining: usize,
+    /// 加算した解析フレーム数
+    count: usize,
+    /// binごとの対数レベルの合計
+    log_sums: Vec<f32>,
+    /// binごとの対数レベルの最大値
+    log_maxima: Vec<f32>,
+    /// RMSの対数の合計
+    log_rms_sum: f32,
+}
+
+/// 無音の区間からノイズプロファイルを作り、音が無い間はゆっくり追従させる
+pub struct NoiseTracker {
+    /// ノイズ測定用の解析器（検出と同じ長い窓）
+    analyzer: Analyzer,
+    calibration: Option<Calibration>,
+}
+
+impl NoiseTracker {
+    pub fn new() -> Self {
+        Self {
+            analyzer: Analyzer::new(FFT_SIZE, 2, WindowKind::BlackmanHarris),
+            calibration: None,
+        }
+    }
+
+    /// キャリブレーションを開始（duration_frames: 測定するフレーム数）
+    pub fn start_calibration(&mut self, duration_frames: usize) {
+        let bins = self.analyzer.magnitudes().len();
+        self.calibration = Some(Calibration {
+            remaining: duration_frames,
+            count: 0,
+            log_sums: vec![0.0; bins],
+            log_maxima: vec![f32::NEG_INFINITY; bins],
+            log_rms_sum: 0.0,
+        });
+    }
+
+    /// キャリブレーション中か
+    pub fn is_calibrating(&self) -> bool {
+        self.calibration.is_some()
+    }
+
+    /// 無音のフレームを加算し、測定時間に達したらプロファイルとゲート・閾値を返す
+    /// （ホップサイズが大きく解析回数が足りなければ、測定時間を過ぎても続ける）
+    /// samples: 最新FFT_SIZEサンプル、new_frames: 前回からの経過フレーム数
+    pub fn calibrate(
+        &mut self,
+        samples: &[f32],
+        new_frames: usize,
+        sample_rate: usize,
+    ) -> Option<NoiseCalibration> {
+        let calibration = self.calibration.as_mut()?;
+        self.analyzer.process(samples);
+        let gain = self.analyzer.noise_gain();
+        for ((sum, max), &m) in calibration
+            .log_sums
+            .iter_mut()
+            .zip(calibration.log_maxima.iter_mut())
+            .zip(self.analyzer.magnitudes())
+        {
+            let log_level = (m / gain).max(MIN_LEVEL).ln();
+            *sum += log_level;
+            *max = max.max(log_level);
+        }
+        calibration.log_rms_sum += rms(samples).max(MIN_LEVEL).ln();
+        calibration.count += 1;
+        calibration.remaining = calibration.remaining.saturating_sub(new_frames);
+        if calibration.remaining > 0 || calibration.count < MIN_CALIBRATION_FRAMES {
+            return None;
+        }
+
+        let calibration = self.calibration.take()?;
+        let count = calibration.count as f32;
+        let log_levels: Vec<f32> = calibration.log_sums.iter().map(|s| s / count).collect();
+        let bin_hz = sample_rate as f32 / self.analyzer.padded_size() as f32;
+
+        // 無音中に各binがプロファイルを最も大きく上回った比率を、誤検出しない閾値とする
+        let min_bin = (CHROMATIC_MIN_FREQUENCY / bin_hz) as usize;
+        let max_bin = ((CHROMATIC_MAX_FREQUENCY / bin_hz) as usize).min(log_levels.len());
+        let max_excess = (min_bin..max_bin)
+            .map(|i| calibration.log_maxima[i] - log_levels[i])
+            .fold(0.0, f32::max);
+        let threshold_ratio =
+            (max_excess.exp() * NOISE_RATIO_MARGIN).clamp(MIN_THRESHOLD_RATIO, MAX_THRESHOLD_RATIO);
+
+        let profile = NoiseProfile {
+            bin_hz,
+            log_levels,
+            log_rms: calibration.log_rms_sum / count,
+        };
+        Some(NoiseCalibration {
+            gate: profile.gate(),
+            threshold_ratio,
+            profile,
+        })
+    }
+
+    /// 音が検出されていない間のフレームでプロファイルを更新（対数領域の指数移動平均）
+    pub fn adapt(
+        &mut self,
+        profile: &mut NoiseProfile,
+        samples: &[f32],
+        new_frames: usize,
+        sample_rate: usize,
+    ) {
+        let elapsed = new_frames as f32 / sample_rate as f32;
+        let alpha = 1.0 - (-elapsed / NOISE_ADAPT_TIME_SECS).exp();
+        self.analyzer.process(samples);
+        let gain = self.analyzer.noise_gain();
+        for (log_level, &m) in profile
+            .log_levels
+            .iter_mut()
+            .zip(self.analyzer.magnitudes())
+        {
+            *log_level += alpha * ((m / gain).max(MIN_LEVEL).ln() - *log_level);
+        }
+        profile.log_rms += alpha * (rms(samples).max(MIN_LEVEL).ln() - profile.log_rms);
+    }
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    (samples.iter().map(|x| x * x).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::test_signals::{harmonic_tone, Lcg};
+
+    /// 一様乱数のノイズ + 指定した周波数のハム
+    fn noise(len: usize, amplitude: f32, hum: Option<f32>, rng: &mut Lcg) -> Vec<f32> {
+        let mut samples = hum.map_or_else(
+            || vec![0.0; len],
+            |f| harmonic_tone(f as f64, &[0.05], 0.0, 0.0, 48000, len),
+        );
+        rng.add_noise(&mut samples, amplitude);
+        samples
+    }
+
+    fn calibrate(
+        tracker: &mut NoiseTracker,
+        amplitude: f32,
+        hum: Option<f32>,
+        rng: &mut Lcg,
+    ) -> NoiseCalibration {
+        tracker.start_calibration(3 * 48000);
+        loop {
+            let samples = noise(FFT_SIZE, amplitude, hum, rng);
+            if let Some(result) = tracker.calibrate(&samples, 2048, 48000) {
+                assert!(!tracker.is_calibrating());
+                return result;
+            }
+        }
+    }
+
+    #[test]
+    fn test_calibration_sets_gate_and_ratio() {
+        let mut tracker = NoiseTracker::new();
+        let mut rng = Lcg::new(0x1357_9bdf);
+        let result = calibrate(&mut tracker, 0.01, None, &mut rng);
+
+        // ゲートはノイズのRMS（一様分布: 振幅/√12）より上
+        let noise_rms = 0.01 / 12f32.sqrt();
+        assert!(result.gate > noise_rms, "gate = {}", result.gate);
+        assert!(result.gate < noise_rms * 4.0, "gate = {}", result.gate);
+        assert!((MIN_THRESHOLD_RATIO..=MAX_THRESHOLD_RATIO).contains(&result.threshold_ratio));
+
+        // 白色ノイズなのでプロファイルは平坦で、正規化したレベルはノイズのRMS程度
+        for freq in [100.0, 1000.0, 4000.0] {
+            let level = result.profile.level_at(freq);
+            assert!(
+                level > noise_rms * 0.3 && level < noise_rms * 1.5,
+                "{} Hz: {}",
+                freq,
+                level
+            );
+        }
+
+        // 新しいノイズのフレームは閾値を超えない
+        let mut analyzer = Analyzer::new(FFT_SIZE, 2, WindowKind::Hann);
+        analyzer.process(&noise(FFT_SIZE, 0.01, None, &mut rng));
+        let bin_hz = 48000.0 / analyzer.padded_size() as f32;
+        let gain = analyzer.noise_gain();
+        let worst = analyzer
+            .magnitudes()
+            .iter()
+            .enumerate()
+            .skip((CHROMATIC_MIN_FREQUENCY / bin_hz) as usize)
+            .take(((CHROMATIC_MAX_FREQUENCY - CHROMATIC_MIN_FREQUENCY) / bin_hz) as usize)
+            .map(|(i, &m)| m / (result.profile.level_at(i as f32 * bin_hz) * gain))
+            .fold(0.0, f32::max);
+        assert!(
+            worst < result.threshold_ratio,
+            "{} >= {}",
+            worst,
+            result.threshold_ratio
+        );
+    }
+
+    #[test]
+    fn test_profile_follows_hum() {
+        let mut tracker = NoiseTracker::new();
+        let mut rng = Lcg::new(0x2468_ace0);
+        let result = calibrate(&mut tracker, 0.01, Some(60.0), &mut rng);
+        let hum = result.profile.level_at(60.0);
+        let floor = result.profile.level_at(500.0);
+        assert!(hum > floor * 10.0, "hum = {}, floor = {}", hum, floor);
+    }
+
+    #[test]
+    fn test_adapt_tracks_quieter_noise() {
+        let mut tracker = NoiseTracker::new();
+        let mut rng = Lcg::new(0x0f0f_1234);
+        let mut profile = calibrate(&mut tracker, 0.01, None, &mut rng).profile;
+        let before = profile.level_at(1000.0);
+
+        // 1フレーム分ではほとんど変わらない
+        tracker.adapt(
+            &mut profile,
+            &noise(FFT_SIZE, 0.001, None, &mut rng),
+            4096,
+            48000,
+        );
+        assert!(profile.level_at(1000.0) > before * 0.9);
+
+        // 時定数の数倍の時間が経てば新しいノイズレベルに近づく
+        for _ in 0..50 {
+            tracker.adapt(
+                &mut profile,
+                &noise(FFT_SIZE, 0.001, None, &mut rng),
+                48000,
+                48000,
+            );
+        }
+        let after = profile.level_at(1000.0);
+        assert!(
+            after < before * 0.2,
+            "before = {}, after = {}",
+            before,
+            after
+        );
+        assert!(profile.gate() < 0.001, "gate = {}", profile.gate());
+    }
+
+    #[test]
+    fn test_suppress_removes_noise_and_keeps_tone() {
+        let mut tracker = NoiseTracker::new();
+        let mut rng = Lcg::new(0x7777_0001);
+        let profile = calibrate(&mut tracker, 0.05, None, &mut rng).profile;
+
+        // 騒がしい部屋で鳴らしたA3（220Hz）
+        let mut samples = noise(FFT_SIZE, 0.05, None, &mut rng);
+        for (s, a3) in
+            samples
+                .iter_mut()
+                .zip(harmonic_tone(220.0, &[0.02], 0.0, 0.0, 48000, FFT_SIZE))
+        {
+            *s += a3;
+        }
+        let mut analyzer = Analyzer::new(FFT_SIZE, 2, WindowKind::BlackmanHarris);
+        let original = analyzer.process(&samples).to_vec();
+        let bin_hz = 48000.0 / analyzer.padded_size() as f32;
+        let tone_bin = (220.0 / bin_hz).round() as usize;
+        let median = |m: &[f32]| {
+            let mut sorted = m[200..2000].to_vec();
+            sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            sorted[sorted.len() / 2]
+        };
+
+        for reduction in [NoiseReduction::SpectralSubtraction, NoiseReduction::Wiener] {
+            let mut cleaned = original.clone();
+            profile.suppress(&mut cleaned, bin_hz, analyzer.noise_gain(), reduction);
+            assert!(
+                cleaned[tone_bin] > original[tone_bin] * 0.8,
+                "{}: tone {} -> {}",
+                reduction.name(),
+                original[tone_bin],
+                cleaned[tone_bin]
+            );
+            assert!(
+                median(&cleaned) < median(&original) * 0.3,
+                "{}: noise {} -> {}",
+                reduction.name(),
+                median(&original),
+                median(&cleaned)
+            );
+            assert_eq!(NoiseReduction::from_name(reduction.name()), Some(reduction));
+            assert_eq!(NoiseReduction::from_u32(reduction as u32), reduction);
+        }
+
+        let mut unchanged = original.clone();
+        profile.suppress(
+            &mut unchanged,
+            bin_hz,
+            analyzer.noise_gain(),
+            NoiseReduction::Off,
+        );
+        assert_eq!(unchanged, original);
+    }
+}
@@ -0,0 +1,193 @@
+use crate::constants::{ONSET_FAST_RELEASE_MS, ONSET_MIN_INTERVAL_MS, ONSET_RATIO, ONSET_SLOW_MS};
+
+/// 振幅エンベロープの急な立ち上がりから撥弦（オンセット）を検出する
+/// 速いピークエンベロープが遅いエンベロープのONSET_RATIO倍を超えた時点をオンセットとする。
+/// 届いたサンプルを順に通すストリーム処理で、状態はホップをまたいで保持する
+pub struct OnsetDetector {
+    /// 速いエンベロープの1サンプルあたりの減衰率
+    fast_release: f32,
+    /// 遅いエンベロープの1サンプルあたりの追従率
+    slow_coeff: f32,
+    fast: f32,
+    slow: f32,
+    /// 連続して検出しない最小間隔（サンプル数）
+    min_interval: usize,
+    /// 直前のオンセットからのサンプル数
+    since_onset: usize,
+}
+
+impl OnsetDetector {
+    pub fn new(sample_rate: usize) -> Self {
+        let per_sample = |ms: f32| (-1000.0 / (ms * sample_rate as f32)).exp();
+        Self {
+            fast_release: per_sample(ONSET_FAST_RELEASE_MS),
+            slow_coeff: 1.0 - per_sample(ONSET_SLOW_MS),
+            fast: 0.0,
+            slow: 0.0,
+            min_interval: (ONSET_MIN_INTERVAL_MS * sample_rate as f32 / 1000.0) as usize,
+            since_onset: usize::MAX,
+        }
+    }
+
+    /// エンベロープを捨てる（ストリームが途切れたときなど）
+    pub fn reset(&mut self) {
+        self.fast = 0.0;
+        self.slow = 0.0;
+        self.since_onset = usize::MAX;
+    }
+
+    /// 新しいサンプルを順に調べ、最初のオンセットのsamples内の位置を返す
+    /// min_level: オンセットとみなす振幅の下限（ノイズの揺らぎで反応しないようにする）
+    pub fn process(&mut self, samples: &[f32], min_level: f32) -> Option<usize> {
+        let mut onset = None;
+        for (i, &x) in samples.iter().enumerate() {
+            self.fast = x.abs().max(self.fast * self.fast_release);
+            let rising = self.fast > ONSET_RATIO * self.slow && self.fast > min_level;
+            self.slow += self.slow_coeff * (self.fast - self.slow);
+            self.since_onset = self.since_onset.saturating_add(1);
+            if rising && onset.is_none() && self.since_onset > self.min_interval {
+                onset = Some(i);
+                self.since_onset = 0;
+            }
+        }
+        onset
+    }
+}
+
+/// 撥弦後の解析範囲（前の音とアタックのサンプルを解析窓に含めない）
+/// オンセットからスキップ時間が過ぎた後のサンプルが窓長分たまるまで、その窓長では解析しない
+pub struct OnsetWindow {
+    /// 解析に使える最初のフレーム（累計フレーム数、Noneなら制限なし）
+    start: Option<u64>,
+}
+
+impl OnsetWindow {
+    pub fn new() -> Self {
+        Self { start: None }
+    }
+
+    /// 制限を解除する（ストリームが途切れたときなど）
+    pub fn reset(&mut self) {
+        self.start = None;
+    }
+
+    /// オンセットを記録し、onset_frame + skip_frames より前のサンプルを解析に使わないようにする
+    pub fn mark(&mut self, onset_frame: u64, skip_frames: u64) {
+        self.start = Some(onset_frame + skip_frames);
+    }
+
+    /// end_frameで終わる最新のサンプルのうち、解析に使えるフレーム数（制限がなければusize::MAX）
+    pub fn available(&self, end_frame: u64) -> usize {
+        match self.start {
+            Some(start) => end_frame.saturating_sub(start) as usize,
+            None => usize::MAX,
+        }
+    }
+
+    /// end_frameで終わるwindow_sizeの窓が、オンセット後のサンプルだけで埋まるか
+    pub fn allows(&self, end_frame: u64, window_size: usize) -> bool {
+        self.available(end_frame) >= window_size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{DEFAULT_ONSET_SKIP_MS, FFT_SIZE};
+    use crate::dsp::detector::{FftPeakDetector, PitchDetector, SearchRange};
+    use crate::dsp::test_signals::{harmonic_tone, Lcg};
+
+    const SR: usize = 48000;
+
+    /// 指数減衰する撥弦音（倍音付き）
+    fn pluck(freq: f32, amplitude: f32, len: usize) -> Vec<f32> {
+        harmonic_tone(freq as f64, &[1.0, 1.0 / 2.0, 1.0 / 3.0], 0.0, 0.0, SR, len)
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| x * amplitude * (-(i as f32 / SR as f32) / 1.5).exp())
+            .collect()
+    }
+
+    /// ブロックごとに処理して、検出したオンセットの位置（サンプル）を返す
+    fn onsets(detector: &mut OnsetDetector, samples: &[f32], min_level: f32) -> Vec<usize> {
+        samples
+            .chunks(512)
+            .enumerate()
+            .filter_map(|(block, chunk)| {
+                detector
+                    .process(chunk, min_level)
+                    .map(|offset| block * 512 + offset)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_detects_pluck_from_silence_once() {
+        for freq in [41.2, 110.0, 329.63] {
+            let mut detector = OnsetDetector::new(SR);
+            let mut samples = vec![0.0; SR / 4];
+            samples.extend(pluck(freq, 0.5, SR * 2));
+            let found = onsets(&mut detector, &samples, 0.001);
+            assert_eq!(found.len(), 1, "{} Hz: {:?}", freq, found);
+            // 立ち上がりから5ms以内
+            let delay = found[0] as isize - (SR / 4) as isize;
+            assert!((0..240).contains(&delay), "{} Hz: delay = {}", freq, delay);
+        }
+    }
+
+    #[test]
+    fn test_detects_repluck_of_ringing_string() {
+        let mut detector = OnsetDetector::new(SR);
+        let mut samples = pluck(82.41, 0.2, SR);
+        samples.extend(pluck(116.54, 0.5, SR));
+        let found = onsets(&mut detector, &samples, 0.001);
+        assert_eq!(found.len(), 2, "{:?}", found);
+        assert!(found[1] >= SR && found[1] < SR + 240, "{:?}", found);
+    }
+
+    #[test]
+    fn test_ignores_noise_below_min_level() {
+        let mut detector = OnsetDetector::new(SR);
+        let mut samples = vec![0.0; SR];
+        Lcg::new(0x0aaa_5555).add_noise(&mut samples, 0.001);
+        assert!(onsets(&mut detector, &samples, 0.001).is_empty());
+    }
+
+    #[test]
+    fn test_window_after_onset_excludes_previous_note() {
+        // A2が鳴っている途中で半音上のA#2を撥弦し、オンセット後の推定に前の音が混ざらないことを確かめる
+        // （両方の音が窓に入ると主ローブが重なり、間の周波数を推定してしまう）
+        const HOP: usize = 512;
+        let mut samples = pluck(110.0, 0.5, SR);
+        let onset_at = samples.len();
+        samples.extend(pluck(116.54, 0.5, SR));
+        // 実際の入力と同じく弱いノイズを加える
+        Lcg::new(0x2222_7777).add_noise(&mut samples, 0.002);
+
+        let mut onset_detector = OnsetDetector::new(SR);
+        let mut window = OnsetWindow::new();
+        let mut detector = FftPeakDetector::new();
+        let range = SearchRange::unrestricted(60.0, 1000.0);
+        let skip = (DEFAULT_ONSET_SKIP_MS as usize * SR / 1000) as u64;
+        let mut analyzed_after_onset = 0;
+        for end in (HOP..=samples.len()).step_by(HOP) {
+            if let Some(offset) = onset_detector.process(&samples[end - HOP..end], 0.001) {
+                window.mark((end - HOP + offset) as u64, skip);
+            }
+            if end < FFT_SIZE || !window.allows(end as u64, FFT_SIZE) {
+                continue;
+            }
+            let estimate = detector
+                .detect(&samples[end - FFT_SIZE..end], SR, &range)
+                .unwrap();
+            let expected = if end > onset_at { 116.54 } else { 110.0 };
+            let cents = 1200.0 * (estimate.fundamental.unwrap() / expected).log2();
+            assert!(cents.abs() < 5.0, "end {}: cents = {}", end, cents);
+            if end > onset_at {
+                analyzed_after_onset += 1;
+            }
+        }
+        // 新しい音もスキップ時間と窓長の後には解析される
+        assert!(analyzed_after_onset > 0);
+    }
+}
@@ -0,0 +1,315 @@
+use std::sync::atomic::Ordering;
+
+use serde::Serialize;
+
+use crate::constants::{
+    FFT_SIZE, HPS_PRESENCE_RATIO, KAISER_BETA, PEAK_INTERPOLATOR, PEAK_REFINEMENT, WINDOW_FUNCTION,
+};
+use crate::dsp::frequency::{
+    calculate_frequency_bins, PeakEstimator, PeakInterpolator, PeakRefinement,
+};
+use crate::dsp::spectrum::Analyzer;
+use crate::dsp::window::WindowKind;
+
+/// 1本の弦あたりに評価する倍音数
+const STRUM_HARMONICS: usize = 6;
+
+/// 各倍音の探索幅（目標からのセント、±）
+const STRUM_SEARCH_CENTS: f32 = 50.0;
+
+/// 弦ごとの検出結果（strumイベント用）
+#[derive(Debug, Serialize, Clone, Copy)]
+pub struct StringReading {
+    /// 弦番号（1 = 最高音弦）
+    pub string: usize,
+    /// 検出周波数（検出できなければNone）
+    pub detected_freq: Option<f32>,
+    /// 目標周波数からのずれ（セント）
+    pub cents: Option<f32>,
+    /// 検出の信頼度（0.0〜1.0）
+    pub confidence: f32,
+}
+
+/// 倍音ごとの探索結果: (倍音次数, 探索幅内のピーク(bin, 振幅), 他の弦の倍音と重なるか)
+type HarmonicPeak = (usize, Option<(usize, f32)>, bool);
+
+/// ストローク（開放弦の和音）から全弦のピッチを同時に推定する解析器
+pub struct StrumAnalyzer {
+    /// 設定の窓関数 + 2倍のゼロパディング
+    analyzer: Analyzer,
+}
+
+impl StrumAnalyzer {
+    pub fn new() -> Self {
+        Self {
+            analyzer: Analyzer::new(FFT_SIZE, 2, WindowKind::BlackmanHarris),
+        }
+    }
+
+    /// 各弦の目標周波数（低音弦から順）に対してずれを推定（窓関数とピーク補間は設定に従う）
+    pub fn analyze(
+        &mut self,
+        samples: &[f32],
+        sample_rate: usize,
+        targets: &[f32],
+        threshold_ratio: f32,
+    ) -> Vec<StringReading> {
+        let window_kind = WindowKind::from_u32(
+            WINDOW_FUNCTION.load(Ordering::SeqCst),
+            *KAISER_BETA.read().unwrap(),
+        );
+        let estimator = PeakEstimator {
+            interpolator: PeakInterpolator::from_u32(PEAK_INTERPOLATOR.load(Ordering::SeqCst)),
+            refinement: PeakRefinement::from_u32(PEAK_REFINEMENT.load(Ordering::SeqCst)),
+        };
+        self.analyze_with(
+            samples,
+            sample_rate,
+            targets,
+            threshold_ratio,
+            window_kind,
+            estimator,
+        )
+    }
+
+    /// 窓関数とピーク補間を指定して各弦のずれを推定
+    /// 他の弦の倍音と重なる倍音は避け、重ならない最も低次の倍音から基音を求める
+    pub fn analyze_with(
+        &mut self,
+        samples: &[f32],
+        sample_rate: usize,
+        targets: &[f32],
+        threshold_ratio: f32,
+        window_kind: WindowKind,
+        estimator: PeakEstimator,
+    ) -> Vec<StringReading> {
+        let string_count = targets.len();
+        let undetected = |index: usize| StringReading {
+            string: string_count - index,
+            detected_freq: None,
+            cents: None,
+            confidence: 0.0,
+        };
+        if samples.len() < FFT_SIZE || targets.is_empty() {
+            return (0..string_count).map(undetected).collect();
+        }
+
+        self.analyzer.set_window(window_kind);
+        let padded_size = self.analyzer.padded_size();
+        self.analyzer.process(samples);
+
+        // ノイズフロアは全弦の倍音を含む帯域の中央値
+        let lowest = targets.iter().copied().fold(f32::INFINITY, f32::min);
+        let highest = targets.iter().copied().fold(0.0, f32::max);
+        let (band_min, band_max) = calculate_frequency_bins(
+            sample_rate,
+            padded_size,
+            lowest * cents_ratio(-STRUM_SEARCH_CENTS),
+            highest * STRUM_HARMONICS as f32 * cents_ratio(STRUM_SEARCH_CENTS),
+        );
+        let noise_floor = self.analyzer.noise_floor(band_min..band_max);
+        let magnitudes = self.analyzer.magnitudes();
+        // 倍音ごとの探索ではノイズの局所ピークも拾うため、単一ピークの判定より厳しい閾値にする
+        let gate = noise_floor * threshold_ratio * HPS_PRESENCE_RATIO;
+
+        targets
+            .iter()
+            .enumerate()
+            .map(|(index, &target)| {
+                let others: Vec<f32> = targets
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, _)| j != index)
+                    .map(|(_, &f)| f)
+                    .collect();
+
+                // 倍音ごとに探索幅内のピークを求める
+                let peaks: Vec<HarmonicPeak> = (1..=STRUM_HARMONICS)
+                    .map(|h| {
+                        let center = target * h as f32;
+                        let (min_bin, max_bin) = calculate_frequency_bins(
+                            sample_rate,
+                            padded_size,
+                            center * cents_ratio(-STRUM_SEARCH_CENTS),
+                            center * cents_ratio(STRUM_SEARCH_CENTS),
+                        );
+                        let peak = (min_bin..max_bin.min(magnitudes.len()))
+                            .map(|i| (i, magnitudes[i]))
+                            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+                        (h, peak, collides(center, &others))
+                    })
+                    .collect();
+
+                // 閾値を超える倍音のうち、重ならない最も低次のものを使う
+                // すべての倍音が他の弦と重なる場合のみ、最も強い倍音で代用する
+                let present = |peak: &Option<(usize, f32)>| peak.is_some_and(|(_, v)| v > gate);
+                let all_shared = peaks.iter().all(|(_, _, shared)| *shared);
+                let chosen = if all_shared {
+                    peaks
+                        .iter()
+                        .filter(|(_, peak, _)| present(peak))
+                        .max_by(|a, b| a.1.unwrap().1.partial_cmp(&b.1.unwrap().1).unwrap())
+                } else {
+                    peaks
+                        .iter()
+                        .find(|(_, peak, shared)| !shared && present(peak))
+                };
+                let Some(&(harmonic, Some((peak_bin, _)), _)) = chosen else {
+                    return undetected(index);
+                };
+
+                let detected =
+                    estimator.frequency(&self.analyzer, peak_bin, sample_rate) / harmonic as f32;
+
+                // 信頼度: 重ならない倍音のうちノイズフロアを十分上回るものの割合（低次倍音ほど重み大）
+                // 重なる倍音で代用した場合は半分にする
+                let (weighted, total) = peaks
+                    .iter()
+                    .filter(|(_, _, shared)| all_shared || !shared)
+                    .fold((0.0f32, 0.0f32), |(p, t), (h, peak, _)| {
+                        let weight = 1.0 / *h as f32;
+                        let is_present =
+                            peak.is_some_and(|(_, v)| v > noise_floor * HPS_PRESENCE_RATIO);
+                        (if is_present { p + weight } else { p }, t + weight)
+                    });
+                let confidence = if all_shared {
+                    0.5 * weighted / total
+                } else {
+                    weighted / total
+                };
+
+                StringReading {
+                    string: string_count - index,
+                    detected_freq: Some(detected),
+                    cents: Some(1200.0 * (detected / target).log2()),
+                    confidence,
+                }
+            })
+            .collect()
+    }
+}
+
+/// セントを周波数比に変換
+fn cents_ratio(cents: f32) -> f32 {
+    2.0_f32.powf(cents / 1200.0)
+}
+
+/// 周波数が他の弦のいずれかの倍音の探索幅に入るか
+fn collides(freq: f32, others: &[f32]) -> bool {
+    others.iter().any(|&other| {
+        (1..=STRUM_HARMONICS)
+            .any(|k| (1200.0 * (freq / (other * k as f32)).log2()).abs() < STRUM_SEARCH_CENTS)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::test_signals::{harmonic_tone, Lcg};
+    use crate::tuning::standard_tuning;
+
+    /// 各弦を指定セントずらした開放弦ストロークの合成信号
+    fn strum(targets: &[f32], detune: &[f32], sample_rate: usize, len: usize) -> Vec<f32> {
+        let amplitudes: Vec<f64> = (1..=STRUM_HARMONICS).map(|h| 0.2 / h as f64).collect();
+        let mut samples = vec![0.0; len];
+        for (s, (&f, &c)) in targets.iter().zip(detune).enumerate() {
+            let freq = f as f64 * 2.0_f64.powf(c as f64 / 1200.0);
+            let string = harmonic_tone(freq, &amplitudes, s as f64, 0.3, sample_rate, len);
+            for (x, y) in samples.iter_mut().zip(string) {
+                *x += y;
+            }
+        }
+        Lcg::new(0x1234_5678).add_noise(&mut samples, 0.01);
+        samples
+    }
+
+    #[test]
+    fn test_strum_reports_every_string() {
+        let targets = standard_tuning().frequencies();
+        let detune = [5.0, -7.0, 0.0, 3.0, -4.0, 8.0];
+        let samples = strum(&targets, &detune, 48000, FFT_SIZE);
+
+        let readings = StrumAnalyzer::new().analyze(&samples, 48000, &targets, 2.0);
+        assert_eq!(readings.len(), 6);
+        for (index, (reading, expected)) in readings.iter().zip(detune).enumerate() {
+            assert_eq!(reading.string, 6 - index);
+            let cents = reading.cents.unwrap();
+            assert!(
+                (cents - expected).abs() < 1.0,
+                "string {}: {} != {}",
+                reading.string,
+                cents,
+                expected
+            );
+            assert!(reading.confidence > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_strum_follows_window_and_interpolator_settings() {
+        let targets = standard_tuning().frequencies();
+        let detune = [-6.0, 4.0, 2.0, -3.0, 7.0, -5.0];
+        let samples = strum(&targets, &detune, 48000, FFT_SIZE);
+
+        // 同じ解析器で窓関数・補間方法を切り替えても、各弦のずれを推定できる
+        let mut analyzer = StrumAnalyzer::new();
+        for (window_kind, interpolator, refinement) in [
+            (
+                WindowKind::Hann,
+                PeakInterpolator::Parabolic,
+                PeakRefinement::Interpolation,
+            ),
+            (
+                WindowKind::Kaiser(9.0),
+                PeakInterpolator::Gaussian,
+                PeakRefinement::Zoom,
+            ),
+            (
+                WindowKind::BlackmanHarris,
+                PeakInterpolator::Quinn2,
+                PeakRefinement::Interpolation,
+            ),
+        ] {
+            let estimator = PeakEstimator {
+                interpolator,
+                refinement,
+            };
+            let readings =
+                analyzer.analyze_with(&samples, 48000, &targets, 2.0, window_kind, estimator);
+            for (reading, expected) in readings.iter().zip(detune) {
+                let cents = reading.cents.unwrap();
+                assert!(
+                    (cents - expected).abs() < 1.0,
+                    "{} / {}: string {}: {} != {}",
+                    window_kind.name(),
+                    interpolator.name(),
+                    reading.string,
+                    cents,
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_strum_missing_string_is_undetected() {
+        // 1弦（E4、readings[5]）を鳴らさない: 基音はE2・A2の倍音と重なるが、
+        // 重ならない倍音（4倍音など）が閾値を超えないので未検出
+        let targets = standard_tuning().frequencies();
+        let samples = strum(&targets[..5], &[0.0; 5], 48000, FFT_SIZE);
+
+        let readings = StrumAnalyzer::new().analyze(&samples, 48000, &targets, 2.0);
+        assert_eq!(readings[5].string, 1);
+        assert!(readings[0].detected_freq.is_some());
+        assert!(readings[5].detected_freq.is_none());
+        assert_eq!(readings[5].confidence, 0.0);
+    }
+
+    #[test]
+    fn test_collides_with_lower_string_harmonics() {
+        let targets = standard_tuning().frequencies();
+        // B3はE2の第3倍音とほぼ一致、G3は重ならない
+        assert!(collides(targets[4], &targets[..4]));
+        assert!(!collides(targets[3], &targets[..3]));
+    }
+}
@@ -0,0 +1,368 @@
+use std::f64::consts::PI;
+
+use crate::constants::{
+    DC_BLOCK_CUTOFF_HZ, HUM_DETECT_RATIO, HUM_HARMONICS, HUM_NOTCH_BANDWIDTH_HZ,
+};
+
+/// 電源ハム除去の動作モード
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HumFilterMode {
+    /// 前処理しない
+    Off = 0,
+    /// 電源周波数を自動判定して除去
+    Auto = 1,
+    /// 50Hzとその倍音を除去
+    Hz50 = 2,
+    /// 60Hzとその倍音を除去
+    Hz60 = 3,
+}
+
+impl HumFilterMode {
+    /// 数値から変換（範囲外はオフ）
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => Self::Auto,
+            2 => Self::Hz50,
+            3 => Self::Hz60,
+            _ => Self::Off,
+        }
+    }
+
+    /// 設定ファイル用の名前から変換
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "off" => Some(Self::Off),
+            "auto" => Some(Self::Auto),
+            "50hz" => Some(Self::Hz50),
+            "60hz" => Some(Self::Hz60),
+            _ => None,
+        }
+    }
+
+    /// 設定ファイル用の名前
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Auto => "auto",
+            Self::Hz50 => "50hz",
+            Self::Hz60 => "60hz",
+        }
+    }
+}
+
+/// 2次IIRフィルタ（転置直接形II、狭帯域のノッチでも安定するよう倍精度で計算）
+#[derive(Debug, Clone)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    z: [f64; 2],
+}
+
+impl Biquad {
+    /// 中心周波数freq、帯域幅bandwidth（Hz）のノッチフィルタ
+    fn notch(freq: f64, bandwidth: f64, sample_rate: f64) -> Self {
+        let w0 = 2.0 * PI * freq / sample_rate;
+        let q = freq / bandwidth;
+        let alpha = w0.sin() / (2.0 * q);
+        let a0 = 1.0 + alpha;
+        let cos = w0.cos();
+        Self {
+            b: [1.0 / a0, -2.0 * cos / a0, 1.0 / a0],
+            a: [-2.0 * cos / a0, (1.0 - alpha) / a0],
+            z: [0.0; 2],
+        }
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[0] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[1] * y;
+        y
+    }
+}
+
+/// 1サンプルずつ更新するGoertzelアルゴリズム（電源周波数の判定用）
+#[derive(Debug, Clone)]
+struct Goertzel {
+    coeff: f64,
+    s1: f64,
+    s2: f64,
+}
+
+impl Goertzel {
+    fn new(freq: f64, sample_rate: f64) -> Self {
+        Self {
+            coeff: 2.0 * (2.0 * PI * freq / sample_rate).cos(),
+            s1: 0.0,
+            s2: 0.0,
+        }
+    }
+
+    fn push(&mut self, x: f64) {
+        let s0 = x + self.coeff * self.s1 - self.s2;
+        self.s2 = self.s1;
+        self.s1 = s0;
+    }
+
+    /// これまでのパワーを返して状態をリセット
+    fn take_power(&mut self) -> f64 {
+        let power = self.s1 * self.s1 + self.s2 * self.s2 - self.coeff * self.s1 * self.s2;
+        self.s1 = 0.0;
+        self.s2 = 0.0;
+        power
+    }
+}
+
+/// 50Hz系・60Hz系の倍音と、その中間（ハムの無い参照周波数）のパワーを測る
+struct MainsDetector {
+    hz50: Vec<Goertzel>,
+    hz60: Vec<Goertzel>,
+    reference: Vec<Goertzel>,
+    /// 判定に使うサンプル数（1秒、周波数分解能1Hz）
+    block_len: usize,
+    count: usize,
+}
+
+impl MainsDetector {
+    fn new(sample_rate: usize) -> Self {
+        let sr = sample_rate as f64;
+        let bank = |base: f64| -> Vec<Goertzel> {
+            (1..=HUM_HARMONICS)
+                .map(|k| Goertzel::new(base * k as f64, sr))
+                .collect()
+        };
+        Self {
+            hz50: bank(50.0),
+            hz60: bank(60.0),
+            reference: bank(55.0),
+            block_len: sample_rate,
+            count: 0,
+        }
+    }
+
+    /// サンプルを加算し、1秒分たまったら判定した電源周波数を返す（ハムが無ければSome(None)）
+    fn push(&mut self, x: f64) -> Option<Option<u32>> {
+        for g in self
+            .hz50
+            .iter_mut()
+            .chain(self.hz60.iter_mut())
+            .chain(self.reference.iter_mut())
+        {
+            g.push(x);
+        }
+        self.count += 1;
+        if self.count < self.block_len {
+            return None;
+        }
+        self.count = 0;
+        let sum = |bank: &mut [Goertzel]| bank.iter_mut().map(Goertzel::take_power).sum::<f64>();
+        let p50 = sum(&mut self.hz50);
+        let p60 = sum(&mut self.hz60);
+        let reference = sum(&mut self.reference);
+        let ratio = HUM_DETECT_RATIO as f64;
+        Some(if p50 > ratio * p60.max(reference) {
+            Some(50)
+        } else if p60 > ratio * p50.max(reference) {
+            Some(60)
+        } else {
+            None
+        })
+    }
+
+    fn reset(&mut self) {
+        for g in self
+            .hz50
+            .iter_mut()
+            .chain(self.hz60.iter_mut())
+            .chain(self.reference.iter_mut())
+        {
+            g.take_power();
+        }
+        self.count = 0;
+    }
+}
+
+/// 窓かけ前の前処理（DC除去の高域通過 + 電源ハムとその倍音のノッチ）
+/// 届いたサンプルを順に通すストリーム処理で、フィルタの状態はホップをまたいで保持する
+pub struct PreFilter {
+    sample_rate: usize,
+    /// DC除去フィルタの係数と状態（y[n] = x[n] - x[n-1] + r*y[n-1]）
+    dc_coeff: f64,
+    dc_x: f64,
+    dc_y: f64,
+    /// 現在のノッチの電源周波数（Noneならノッチなし）
+    mains: Option<u32>,
+    notches: Vec<Biquad>,
+    detector: MainsDetector,
+}
+
+impl PreFilter {
+    pub fn new(sample_rate: usize) -> Self {
+        Self {
+            sample_rate,
+            dc_coeff: 1.0 - 2.0 * PI * DC_BLOCK_CUTOFF_HZ as f64 / sample_rate as f64,
+            dc_x: 0.0,
+            dc_y: 0.0,
+            mains: None,
+            notches: Vec::new(),
+            detector: MainsDetector::new(sample_rate),
+        }
+    }
+
+    /// ノッチを適用している電源周波数（Hz）
+    pub fn mains_frequency(&self) -> Option<u32> {
+        self.mains
+    }
+
+    /// フィルタの状態を捨てる（ストリームが途切れたときなど）
+    pub fn reset(&mut self) {
+        self.dc_x = 0.0;
+        self.dc_y = 0.0;
+        for notch in &mut self.notches {
+            notch.z = [0.0; 2];
+        }
+        self.detector.reset();
+    }
+
+    /// ノッチの電源周波数を切り替える（変わったときだけ作り直す）
+    fn set_mains(&mut self, mains: Option<u32>) {
+        if mains == self.mains {
+            return;
+        }
+        self.mains = mains;
+        let sr = self.sample_rate as f64;
+        self.notches = mains
+            .map(|base| {
+                (1..=HUM_HARMONICS)
+                    .map(|k| base as f64 * k as f64)
+                    .filter(|&freq| freq < sr / 2.0)
+                    .map(|freq| Biquad::notch(freq, HUM_NOTCH_BANDWIDTH_HZ as f64, sr))
+                    .collect()
+            })
+            .unwrap_or_default();
+    }
+
+    /// samplesをその場でフィルタする
+    /// detect_mains: 自動モードで電源周波数を判定してよいか（音が鳴っている間は判定しない）
+    pub fn process(&mut self, samples: &mut [f32], mode: HumFilterMode, detect_mains: bool) {
+        match mode {
+            HumFilterMode::Off => return,
+            HumFilterMode::Hz50 => self.set_mains(Some(50)),
+            HumFilterMode::Hz60 => self.set_mains(Some(60)),
+            HumFilterMode::Auto => {}
+        }
+        let detect = mode == HumFilterMode::Auto && detect_mains;
+        if !detect {
+            self.detector.reset();
+        }
+
+        for sample in samples.iter_mut() {
+            // DC除去
+            let x = *sample as f64;
+            let mut y = x - self.dc_x + self.dc_coeff * self.dc_y;
+            self.dc_x = x;
+            self.dc_y = y;
+
+            if detect {
+                if let Some(mains) = self.detector.push(y) {
+                    // ハムが見つからなかったブロックでは直前の判定を維持する
+                    if mains.is_some() {
+                        self.set_mains(mains);
+                    }
+                }
+            }
+            for notch in &mut self.notches {
+                y = notch.process(y);
+            }
+            *sample = y as f32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::test_signals::{harmonic_tone, Lcg};
+
+    const SR: usize = 48000;
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|x| x * x).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    /// 2秒間フィルタを通して定常状態にしてから、次の0.5秒のRMSを返す
+    fn settled_rms(filter: &mut PreFilter, mode: HumFilterMode, freq: f32) -> f32 {
+        let mut samples = harmonic_tone(freq as f64, &[0.1], 0.0, 0.0, SR, SR * 2 + SR / 2);
+        let (head, tail) = samples.split_at_mut(SR * 2);
+        filter.process(head, mode, false);
+        filter.process(tail, mode, false);
+        rms(tail)
+    }
+
+    #[test]
+    fn test_notch_removes_hum_and_keeps_notes() {
+        let input = 0.1 / 2f32.sqrt();
+        for freq in [60.0, 120.0, 180.0] {
+            let mut filter = PreFilter::new(SR);
+            let out = settled_rms(&mut filter, HumFilterMode::Hz60, freq);
+            assert!(out < input * 0.03, "{} Hz: {}", freq, out);
+        }
+        // ハムに近いA2（110Hz）、D3（146.8Hz）はほとんど減衰しない
+        for freq in [110.0, 146.83] {
+            let mut filter = PreFilter::new(SR);
+            let out = settled_rms(&mut filter, HumFilterMode::Hz50, freq);
+            assert!(out > input * 0.9, "{} Hz: {}", freq, out);
+        }
+    }
+
+    #[test]
+    fn test_dc_is_blocked() {
+        let mut filter = PreFilter::new(SR);
+        let mut samples = vec![0.5f32; SR];
+        filter.process(&mut samples, HumFilterMode::Auto, true);
+        assert!(samples[SR - 1].abs() < 1e-3, "{}", samples[SR - 1]);
+    }
+
+    #[test]
+    fn test_auto_detects_mains_frequency() {
+        for (base, other) in [(50.0, 60u32), (60.0, 50u32)] {
+            let mut filter = PreFilter::new(SR);
+            let mut samples =
+                harmonic_tone(base, &[0.02, 0.02 / 2.0, 0.02 / 3.0], 0.0, 0.0, SR, SR * 2);
+            Lcg::new(0x5eed_1234).add_noise(&mut samples, 0.005);
+
+            // 音が鳴っている間は判定しない
+            filter.process(&mut samples.clone(), HumFilterMode::Auto, false);
+            assert_eq!(filter.mains_frequency(), None);
+
+            filter.process(&mut samples, HumFilterMode::Auto, true);
+            assert_eq!(filter.mains_frequency(), Some(base as u32));
+            assert_ne!(filter.mains_frequency(), Some(other));
+        }
+    }
+
+    #[test]
+    fn test_off_passes_through() {
+        let mut filter = PreFilter::new(SR);
+        let original: Vec<f32> = harmonic_tone(60.0, &[0.1], 0.0, 0.0, SR, 4096)
+            .into_iter()
+            .map(|x| x + 0.2)
+            .collect();
+        let mut samples = original.clone();
+        filter.process(&mut samples, HumFilterMode::Off, true);
+        assert_eq!(samples, original);
+    }
+
+    #[test]
+    fn test_hum_filter_mode_name_round_trip() {
+        for mode in [
+            HumFilterMode::Off,
+            HumFilterMode::Auto,
+            HumFilterMode::Hz50,
+            HumFilterMode::Hz60,
+        ] {
+            assert_eq!(HumFilterMode::from_name(mode.name()), Some(mode));
+            assert_eq!(HumFilterMode::from_u32(mode as u32), mode);
+        }
+    }
+}
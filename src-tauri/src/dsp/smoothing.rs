@@ -0,0 +1,288 @@
+use std::collections::VecDeque;
+
+use crate::constants::{MEDIAN_SMOOTHING_LENGTH, SMOOTHING_RESET_CENTS};
+
+/// 検出した周波数の平滑化方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PitchSmoothing {
+    /// 直近の検出値の中央値（外れ値に強いが、遅れと階段状の変化が出る）
+    Median = 0,
+    /// 1次の指数移動平均
+    Ema = 1,
+    /// 対数周波数（セント）上の1次元カルマンフィルタ
+    Kalman = 2,
+}
+
+impl PitchSmoothing {
+    /// 数値から変換（範囲外は中央値）
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => Self::Ema,
+            2 => Self::Kalman,
+            _ => Self::Median,
+        }
+    }
+
+    /// 設定ファイル用の名前から変換
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "median" => Some(Self::Median),
+            "ema" => Some(Self::Ema),
+            "kalman" => Some(Self::Kalman),
+            _ => None,
+        }
+    }
+
+    /// 設定ファイル用の名前
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Median => "median",
+            Self::Ema => "ema",
+            Self::Kalman => "kalman",
+        }
+    }
+}
+
+/// 平滑化の設定
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SmoothingConfig {
+    pub kind: PitchSmoothing,
+    /// EMAの係数（0〜1、大きいほど新しい値に速く追従）
+    pub ema_alpha: f32,
+    /// カルマンフィルタのプロセスノイズ（1回の解析あたりの音程の揺れの分散、セント²）
+    pub process_noise: f32,
+    /// カルマンフィルタの観測ノイズ（検出値の誤差の分散、セント²）
+    pub measurement_noise: f32,
+}
+
+/// 検出した周波数を平滑化する
+/// EMA・カルマンフィルタは音が変わった（直前の検出値から大きく離れた）ときに前の音と混ぜないようリセットする
+/// 中央値は1つだけの外れ値で履歴を捨てないようリセットせず、新しい音が過半数になった時点で追従する
+pub struct PitchSmoother {
+    config: SmoothingConfig,
+    /// 中央値用の直近の検出値（Hz）
+    history: VecDeque<f32>,
+    /// EMA・カルマンフィルタの推定値（セント）と分散
+    estimate: f32,
+    variance: f32,
+    /// リセット後に受け取った検出値の数
+    count: usize,
+    /// 直前の検出値（セント、音の変化の判定用）
+    last: f32,
+}
+
+impl PitchSmoother {
+    pub fn new(config: SmoothingConfig) -> Self {
+        Self {
+            config,
+            history: VecDeque::with_capacity(MEDIAN_SMOOTHING_LENGTH),
+            estimate: 0.0,
+            variance: 0.0,
+            count: 0,
+            last: 0.0,
+        }
+    }
+
+    /// 設定を反映（平滑化方法が変わったときは履歴を捨てる）
+    pub fn configure(&mut self, config: SmoothingConfig) {
+        if config.kind != self.config.kind {
+            self.reset();
+        }
+        self.config = config;
+    }
+
+    /// 履歴を捨てる（新しい音の立ち上がりやモード切り替え時）
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.count = 0;
+    }
+
+    /// 検出値を追加し、2つ目以降なら平滑化した周波数を返す（1つだけの外れ値は送らない）
+    pub fn push(&mut self, freq: f32) -> Option<f32> {
+        let cents = to_cents(freq);
+        if self.config.kind != PitchSmoothing::Median
+            && self.count > 0
+            && (cents - self.last).abs() > SMOOTHING_RESET_CENTS
+        {
+            self.reset();
+        }
+        self.last = cents;
+        self.count += 1;
+
+        match self.config.kind {
+            PitchSmoothing::Median => {
+                if self.history.len() == MEDIAN_SMOOTHING_LENGTH {
+                    self.history.pop_front();
+                }
+                self.history.push_back(freq);
+            }
+            PitchSmoothing::Ema => {
+                if self.count == 1 {
+                    self.estimate = cents;
+                } else {
+                    let alpha = self.config.ema_alpha.clamp(0.0, 1.0);
+                    self.estimate += alpha * (cents - self.estimate);
+                }
+            }
+            PitchSmoothing::Kalman => {
+                let r = self.config.measurement_noise.max(f32::MIN_POSITIVE);
+                if self.count == 1 {
+                    self.estimate = cents;
+                    self.variance = r;
+                } else {
+                    // 予測（音程はランダムウォークするとみなす）
+                    self.variance += self.config.process_noise.max(0.0);
+                    // 更新
+                    let gain = self.variance / (self.variance + r);
+                    self.estimate += gain * (cents - self.estimate);
+                    self.variance *= 1.0 - gain;
+                }
+            }
+        }
+
+        if self.count < 2 {
+            return None;
+        }
+        Some(match self.config.kind {
+            PitchSmoothing::Median => {
+                let mut sorted: Vec<f32> = self.history.iter().copied().collect();
+                sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                sorted[sorted.len() / 2]
+            }
+            PitchSmoothing::Ema | PitchSmoothing::Kalman => from_cents(self.estimate),
+        })
+    }
+}
+
+/// 周波数をセント（1Hz基準の対数周波数）に変換
+fn to_cents(freq: f32) -> f32 {
+    1200.0 * freq.max(f32::MIN_POSITIVE).log2()
+}
+
+fn from_cents(cents: f32) -> f32 {
+    (cents / 1200.0).exp2()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::test_signals::Lcg;
+
+    fn config(kind: PitchSmoothing) -> SmoothingConfig {
+        SmoothingConfig {
+            kind,
+            ema_alpha: 0.4,
+            process_noise: 1.0,
+            measurement_noise: 25.0,
+        }
+    }
+
+    /// 110Hzを中心に±6セントで揺れる検出値
+    fn jittery(n: usize) -> Vec<f32> {
+        let mut rng = Lcg::new(0x1111_2222);
+        (0..n)
+            .map(|_| 110.0 * (rng.uniform() * 12.0 / 1200.0).exp2())
+            .collect()
+    }
+
+    fn cents_error(freq: f32) -> f32 {
+        1200.0 * (freq / 110.0).log2()
+    }
+
+    #[test]
+    fn test_median_of_recent_readings() {
+        let mut smoother = PitchSmoother::new(config(PitchSmoothing::Median));
+        assert_eq!(smoother.push(110.0), None);
+        assert_eq!(smoother.push(110.2), Some(110.2));
+        assert_eq!(smoother.push(109.9), Some(110.0));
+        // 直近5つだけを使う
+        for f in [110.1, 110.3, 110.4, 110.5] {
+            smoother.push(f);
+        }
+        assert_eq!(smoother.push(110.6), Some(110.4));
+    }
+
+    #[test]
+    fn test_filters_reduce_jitter() {
+        for kind in [PitchSmoothing::Ema, PitchSmoothing::Kalman] {
+            let mut smoother = PitchSmoother::new(config(kind));
+            let readings = jittery(200);
+            let outputs: Vec<f32> = readings.iter().filter_map(|&f| smoother.push(f)).collect();
+            assert_eq!(outputs.len(), readings.len() - 1);
+
+            let rms = |values: &[f32]| {
+                (values.iter().map(|&f| cents_error(f).powi(2)).sum::<f32>() / values.len() as f32)
+                    .sqrt()
+            };
+            let raw = rms(&readings[20..]);
+            let smoothed = rms(&outputs[19..]);
+            assert!(
+                smoothed < raw * 0.7,
+                "{}: raw = {}, smoothed = {}",
+                kind.name(),
+                raw,
+                smoothed
+            );
+        }
+    }
+
+    #[test]
+    fn test_median_ignores_single_outlier() {
+        let mut smoother = PitchSmoother::new(config(PitchSmoothing::Median));
+        for _ in 0..5 {
+            smoother.push(110.0);
+        }
+        // 1つだけ大きく外れた値では履歴を捨てない
+        assert_eq!(smoother.push(146.83), Some(110.0));
+        assert_eq!(smoother.push(110.1), Some(110.0));
+        // 新しい音が続けば過半数になった時点で追従する
+        for _ in 0..2 {
+            smoother.push(146.83);
+        }
+        assert_eq!(smoother.push(146.83), Some(146.83));
+    }
+
+    #[test]
+    fn test_resets_on_note_change() {
+        for kind in [PitchSmoothing::Ema, PitchSmoothing::Kalman] {
+            let mut smoother = PitchSmoother::new(config(kind));
+            for _ in 0..10 {
+                smoother.push(110.0);
+            }
+            // 4度上（D3）に変わったら前の音と混ぜない
+            assert_eq!(smoother.push(146.83), None, "{}", kind.name());
+            let next = smoother.push(146.83).unwrap();
+            assert!((next - 146.83).abs() < 0.01, "{}: {}", kind.name(), next);
+        }
+    }
+
+    #[test]
+    fn test_kalman_follows_slow_bend() {
+        // 1回あたり0.5セントずつ上がる音程に遅れずに追従する
+        let mut smoother = PitchSmoother::new(config(PitchSmoothing::Kalman));
+        let mut last = 0.0;
+        for i in 0..100 {
+            let freq = 110.0 * (i as f32 * 0.5 / 1200.0).exp2();
+            if let Some(out) = smoother.push(freq) {
+                last = cents_error(out) - i as f32 * 0.5;
+            }
+        }
+        assert!(last.abs() < 3.0, "lag = {} cents", last);
+    }
+
+    #[test]
+    fn test_configure_resets_when_kind_changes() {
+        let mut smoother = PitchSmoother::new(config(PitchSmoothing::Median));
+        smoother.push(110.0);
+        smoother.push(110.0);
+        smoother.configure(config(PitchSmoothing::Median));
+        assert!(smoother.push(110.0).is_some());
+        smoother.configure(config(PitchSmoothing::Kalman));
+        assert_eq!(smoother.push(110.0), None);
+        assert_eq!(
+            PitchSmoothing::from_name(PitchSmoothing::Kalman.name()),
+            Some(PitchSmoothing::Kalman)
+        );
+        assert_eq!(PitchSmoothing::from_u32(1), PitchSmoothing::Ema);
+    }
+}
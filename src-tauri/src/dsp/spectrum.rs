@@ -0,0 +1,192 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use realfft::num_complex::Complex;
+use realfft::{RealFftPlanner, RealToComplex};
+
+use crate::dsp::frequency::calculate_noise_floor;
+use crate::dsp::window::WindowKind;
+
+/// 窓かけ + ゼロパディング + 実数FFTで振幅スペクトルを求める解析器
+/// FFTプラン・窓関数の係数表・作業バッファを保持し、フレームごとの処理ではアロケーションしない
+pub struct Analyzer {
+    /// 実数→複素数FFTのプラン（パディング後の長さ）
+    fft: Arc<dyn RealToComplex<f32>>,
+    /// 窓関数の種類
+    window_kind: WindowKind,
+    /// 窓関数の係数表（フレーム長）
+    window: Vec<f32>,
+    /// 白色ノイズに対する振幅の利得（窓関数の二乗和の平方根）
+    noise_gain: f32,
+    /// 窓をかける前のフレーム（矩形窓を前提とする補間用）
+    samples: Vec<f32>,
+    /// 窓をかけたフレーム（ズームFFTなど時間領域の再評価用）
+    frame: Vec<f32>,
+    /// FFT入力（窓をかけたフレーム + ゼロパディング、FFTの作業領域としても使われる）
+    input: Vec<f32>,
+    /// FFT出力（0〜ナイキストのbin）
+    spectrum: Vec<Complex<f32>>,
+    /// FFTの作業バッファ
+    scratch: Vec<Complex<f32>>,
+    /// 振幅スペクトル
+    magnitudes: Vec<f32>,
+    /// ノイズフロア計算用の作業バッファ
+    sort_buffer: Vec<f32>,
+}
+
+impl Analyzer {
+    /// frame_size: 窓をかけるサンプル数、padding: ゼロパディングの倍率
+    pub fn new(frame_size: usize, padding: usize, window_kind: WindowKind) -> Self {
+        let padded_size = frame_size * padding.max(1);
+        let fft = RealFftPlanner::<f32>::new().plan_fft_forward(padded_size);
+        let spectrum = fft.make_output_vec();
+        let scratch = fft.make_scratch_vec();
+        let window = window_kind.table(frame_size);
+        Self {
+            fft,
+            window_kind,
+            noise_gain: window_noise_gain(&window),
+            window,
+            samples: vec![0.0; frame_size],
+            frame: vec![0.0; frame_size],
+            input: vec![0.0; padded_size],
+            magnitudes: vec![0.0; spectrum.len()],
+            sort_buffer: Vec::with_capacity(spectrum.len()),
+            spectrum,
+            scratch,
+        }
+    }
+
+    /// 窓をかけるサンプル数
+    pub fn frame_size(&self) -> usize {
+        self.window.len()
+    }
+
+    /// 窓関数を切り替える（変わったときだけ係数表を作り直す）
+    pub fn set_window(&mut self, window_kind: WindowKind) {
+        if window_kind != self.window_kind {
+            self.window = window_kind.table(self.frame_size());
+            self.noise_gain = window_noise_gain(&self.window);
+            self.window_kind = window_kind;
+        }
+    }
+
+    /// ゼロパディング後のFFTサイズ（bin番号と周波数の変換に使う）
+    pub fn padded_size(&self) -> usize {
+        self.input.len()
+    }
+
+    /// samplesの先頭frame_size分を解析し、振幅スペクトル（0〜ナイキスト）を返す
+    pub fn process(&mut self, samples: &[f32]) -> &[f32] {
+        let frame_size = self.frame_size();
+        for (((raw, x), &s), &w) in self
+            .samples
+            .iter_mut()
+            .zip(self.frame.iter_mut())
+            .zip(samples)
+            .zip(&self.window)
+        {
+            *raw = s;
+            *x = s * w;
+        }
+        // 入力はFFTの作業領域として上書きされるので、パディング部分も毎回ゼロに戻す
+        self.input[..frame_size].copy_from_slice(&self.frame);
+        self.input[frame_size..].fill(0.0);
+
+        self.fft
+            .process_with_scratch(&mut self.input, &mut self.spectrum, &mut self.scratch)
+            .expect("buffer sizes match the FFT plan");
+        for (m, c) in self.magnitudes.iter_mut().zip(&self.spectrum) {
+            *m = c.norm();
+        }
+        &self.magnitudes
+    }
+
+    /// 直前に解析した窓をかける前のフレーム
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    /// 直前に解析した窓かけ済みのフレーム
+    pub fn frame(&self) -> &[f32] {
+        &self.frame
+    }
+
+    /// 直前に解析した複素スペクトル（0〜ナイキスト）
+    pub fn spectrum(&self) -> &[Complex<f32>] {
+        &self.spectrum
+    }
+
+    /// 直前に解析した振幅スペクトル
+    pub fn magnitudes(&self) -> &[f32] {
+        &self.magnitudes
+    }
+
+    /// 直前に解析した振幅スペクトルを書き換える（ノイズ除去用）
+    pub fn magnitudes_mut(&mut self) -> &mut [f32] {
+        &mut self.magnitudes
+    }
+
+    /// 白色ノイズに対する振幅の利得（窓長・窓関数によらないノイズレベルとの換算に使う）
+    pub fn noise_gain(&self) -> f32 {
+        self.noise_gain
+    }
+
+    /// 指定したbin範囲の振幅の中央値（ノイズフロア）
+    pub fn noise_floor(&mut self, bins: Range<usize>) -> f32 {
+        calculate_noise_floor(&self.magnitudes[bins], &mut self.sort_buffer)
+    }
+}
+
+/// 窓関数の二乗和の平方根（分散1の白色ノイズに対する各binの振幅の二乗平均平方根）
+fn window_noise_gain(window: &[f32]) -> f32 {
+    window.iter().map(|w| w * w).sum::<f32>().sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::test_signals::harmonic_tone;
+    use rustfft::FftPlanner;
+
+    fn tone(freq: f32, sample_rate: usize, len: usize) -> Vec<f32> {
+        harmonic_tone(freq as f64, &[1.0, 0.3], 0.0, 0.0, sample_rate, len)
+    }
+
+    #[test]
+    fn test_matches_complex_fft() {
+        let samples = tone(196.0, 48000, 1024);
+        let mut analyzer = Analyzer::new(1024, 2, WindowKind::BlackmanHarris);
+        let magnitudes = analyzer.process(&samples).to_vec();
+
+        // 複素FFTで同じ処理をした結果と一致する
+        let window = WindowKind::BlackmanHarris.table(1024);
+        let mut input = vec![Complex { re: 0.0, im: 0.0 }; 2048];
+        for ((c, &s), &w) in input.iter_mut().zip(&samples).zip(&window) {
+            c.re = s * w;
+        }
+        FftPlanner::new().plan_fft_forward(2048).process(&mut input);
+
+        assert_eq!(magnitudes.len(), 1025);
+        for (a, b) in magnitudes.iter().zip(&input) {
+            assert!((a - b.norm()).abs() < 1e-3, "{} != {}", a, b.norm());
+        }
+    }
+
+    #[test]
+    fn test_reuse_does_not_leak_previous_frame() {
+        let mut analyzer = Analyzer::new(1024, 2, WindowKind::BlackmanHarris);
+        analyzer.process(&tone(440.0, 48000, 1024));
+        let silent = analyzer.process(&[0.0; 1024]);
+        assert!(silent.iter().all(|&m| m == 0.0));
+    }
+
+    #[test]
+    fn test_noise_floor_is_median() {
+        let mut analyzer = Analyzer::new(1024, 2, WindowKind::BlackmanHarris);
+        analyzer.process(&tone(330.0, 48000, 1024));
+        let mut sorted = analyzer.magnitudes()[10..200].to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(analyzer.noise_floor(10..200), sorted[95]);
+    }
+}
@@ -0,0 +1,192 @@
+use std::f64::consts::PI;
+
+/// 位相を測るブロック長（サンプル数）
+const STROBE_BLOCK_SIZE: usize = 4096;
+
+/// ブロック間の間隔（サンプル数、位相差が±πを超えない範囲で追跡できるずれは±fs/(2*hop)）
+const STROBE_HOP_SIZE: usize = 1024;
+
+/// 位相追跡の結果
+#[derive(Debug, Clone, Copy)]
+pub struct PhaseMeasurement {
+    /// 位相の傾きから求めた周波数（Hz）
+    pub frequency: f32,
+    /// 基準周波数に対する位相速度（rad/s、正なら基準より高い）
+    pub phase_velocity: f32,
+    /// 位相の直線へのあてはまり（0.0〜1.0、1.0で完全に一定の周波数）
+    pub coherence: f32,
+}
+
+/// ストロボチューナー用の位相追跡器
+/// 基準周波数の直交検波で連続するブロックの位相を求め、その傾き（位相速度）から周波数のずれを測る
+pub struct StrobeTracker {
+    /// ブロックごとのHann窓（キャッシュ）
+    window: Vec<f64>,
+    /// ブロックごとの時刻・位相・振幅の作業領域（測定ごとに確保しないよう再利用する）
+    times: Vec<f64>,
+    phases: Vec<f64>,
+    weights: Vec<f64>,
+}
+
+impl StrobeTracker {
+    pub fn new() -> Self {
+        let n = STROBE_BLOCK_SIZE as f64;
+        Self {
+            window: (0..STROBE_BLOCK_SIZE)
+                .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f64 / n).cos())
+                .collect(),
+            times: Vec::new(),
+            phases: Vec::new(),
+            weights: Vec::new(),
+        }
+    }
+
+    /// 基準周波数に対する位相速度を測定
+    /// reference_freq: 検波に使う周波数（粗い推定値でよい、ずれは位相の傾きとして現れる）
+    pub fn measure(
+        &mut self,
+        samples: &[f32],
+        sample_rate: usize,
+        reference_freq: f32,
+    ) -> Option<PhaseMeasurement> {
+        if samples.len() < STROBE_BLOCK_SIZE + STROBE_HOP_SIZE || reference_freq <= 0.0 {
+            return None;
+        }
+        let omega = 2.0 * PI * reference_freq as f64 / sample_rate as f64;
+
+        // ブロックごとの位相（サンプル位置の絶対値で検波するので、ブロック間で位相が連続する）
+        let Self {
+            window,
+            times,
+            phases,
+            weights,
+        } = self;
+        times.clear();
+        phases.clear();
+        weights.clear();
+        let mut previous: Option<f64> = None;
+        for start in (0..=samples.len() - STROBE_BLOCK_SIZE).step_by(STROBE_HOP_SIZE) {
+            let (mut re, mut im) = (0.0f64, 0.0f64);
+            for (i, (&x, &w)) in samples[start..start + STROBE_BLOCK_SIZE]
+                .iter()
+                .zip(window.iter())
+                .enumerate()
+            {
+                let arg = omega * (start + i) as f64;
+                let v = x as f64 * w;
+                re += v * arg.cos();
+                im -= v * arg.sin();
+            }
+            let magnitude = (re * re + im * im).sqrt();
+            if magnitude <= 0.0 {
+                return None;
+            }
+
+            // 前のブロックとの差が±πに収まるように位相をアンラップ
+            let mut phase = im.atan2(re);
+            if let Some(prev) = previous {
+                phase = prev + wrap_phase(phase - prev);
+            }
+            previous = Some(phase);
+
+            let center = start as f64 + STROBE_BLOCK_SIZE as f64 / 2.0;
+            times.push(center / sample_rate as f64);
+            phases.push(phase);
+            weights.push(magnitude);
+        }
+
+        // 振幅で重み付けした最小二乗法で位相の傾き（rad/s）を求める
+        let total: f64 = weights.iter().sum();
+        let mean_t = times
+            .iter()
+            .zip(weights.iter())
+            .map(|(t, w)| t * w)
+            .sum::<f64>()
+            / total;
+        let mean_p = phases
+            .iter()
+            .zip(weights.iter())
+            .map(|(p, w)| p * w)
+            .sum::<f64>()
+            / total;
+        let (mut cov, mut var) = (0.0f64, 0.0f64);
+        for ((t, p), w) in times.iter().zip(phases.iter()).zip(weights.iter()) {
+            cov += w * (t - mean_t) * (p - mean_p);
+            var += w * (t - mean_t) * (t - mean_t);
+        }
+        if var <= 0.0 {
+            return None;
+        }
+        let slope = cov / var;
+
+        // 直線からの残差の位相のそろい具合
+        let (mut c, mut s) = (0.0f64, 0.0f64);
+        for ((t, p), w) in times.iter().zip(phases.iter()).zip(weights.iter()) {
+            let residual = p - (mean_p + slope * (t - mean_t));
+            c += w * residual.cos();
+            s += w * residual.sin();
+        }
+        let coherence = ((c * c + s * s).sqrt() / total) as f32;
+
+        Some(PhaseMeasurement {
+            frequency: (reference_freq as f64 + slope / (2.0 * PI)) as f32,
+            phase_velocity: slope as f32,
+            coherence,
+        })
+    }
+}
+
+/// 位相を-π〜πに折り返す
+fn wrap_phase(phase: f64) -> f64 {
+    (phase + PI).rem_euclid(2.0 * PI) - PI
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::FFT_SIZE;
+    use crate::dsp::test_signals::{harmonic_tone, Lcg};
+
+    fn tone(freq: f64, amplitudes: &[f64], sample_rate: usize, len: usize) -> Vec<f32> {
+        let mut samples = harmonic_tone(freq, amplitudes, -1.0, 1.0, sample_rate, len);
+        Lcg::new(0x0bad_cafe).add_noise(&mut samples, 0.01);
+        samples
+    }
+
+    #[test]
+    fn test_strobe_resolves_sub_tenth_cent() {
+        // A2を+0.05セントずらし、検波周波数は3セントずれた粗い推定値を使う
+        let target = 110.0f64;
+        let actual = target * 2.0_f64.powf(0.05 / 1200.0);
+        let samples = tone(actual, &[1.0, 0.6, 0.3], 48000, FFT_SIZE);
+        let reference = (target * 2.0_f64.powf(3.0 / 1200.0)) as f32;
+
+        let result = StrobeTracker::new()
+            .measure(&samples, 48000, reference)
+            .unwrap();
+        let cents = 1200.0 * (result.frequency as f64 / target).log2();
+        assert!((cents - 0.05).abs() < 0.01, "cents = {}", cents);
+        assert!(result.phase_velocity < 0.0);
+        assert!(result.coherence > 0.99);
+    }
+
+    #[test]
+    fn test_strobe_phase_velocity_matches_offset() {
+        // 基準より0.5Hz高い音: 位相速度は2π×0.5 rad/s
+        let samples = tone(196.5, &[1.0, 0.5], 44100, FFT_SIZE);
+        let result = StrobeTracker::new()
+            .measure(&samples, 44100, 196.0)
+            .unwrap();
+        assert!(
+            (result.phase_velocity - PI as f32).abs() < 0.01,
+            "phase_velocity = {}",
+            result.phase_velocity
+        );
+    }
+
+    #[test]
+    fn test_wrap_phase() {
+        assert!((wrap_phase(3.0 * PI / 2.0) + PI / 2.0).abs() < 1e-12);
+        assert!((wrap_phase(-3.0 * PI / 2.0) - PI / 2.0).abs() < 1e-12);
+    }
+}
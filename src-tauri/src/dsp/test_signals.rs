@@ -0,0 +1,54 @@
+//! テスト用の合成信号（正弦波・倍音を含む音・再現できるノイズ）
+
+use std::f64::consts::PI;
+
+/// 再現できる一様乱数（線形合同法）
+pub struct Lcg(u32);
+
+impl Lcg {
+    pub fn new(seed: u32) -> Self {
+        Self(seed)
+    }
+
+    /// -0.5〜0.5の一様乱数
+    pub fn uniform(&mut self) -> f32 {
+        self.0 = self.0.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (self.0 >> 8) as f32 / (1u32 << 24) as f32 - 0.5
+    }
+
+    /// 振れ幅amplitudeの一様ノイズを加える
+    pub fn add_noise(&mut self, samples: &mut [f32], amplitude: f32) {
+        for x in samples.iter_mut() {
+            *x += self.uniform() * amplitude;
+        }
+    }
+}
+
+/// 振幅1の正弦波
+pub fn sine(freq: f32, sample_rate: usize, len: usize) -> Vec<f32> {
+    harmonic_tone(freq as f64, &[1.0], 0.0, 0.0, sample_rate, len)
+}
+
+/// 倍音を含む音（amplitudes[k]は第k+1倍音の振幅、第h倍音の初期位相は phase + phase_step * h）
+pub fn harmonic_tone(
+    freq: f64,
+    amplitudes: &[f64],
+    phase: f64,
+    phase_step: f64,
+    sample_rate: usize,
+    len: usize,
+) -> Vec<f32> {
+    (0..len)
+        .map(|i| {
+            let t = i as f64 / sample_rate as f64;
+            amplitudes
+                .iter()
+                .enumerate()
+                .map(|(k, a)| {
+                    let harmonic = (k + 1) as f64;
+                    a * (2.0 * PI * freq * harmonic * t + phase + phase_step * harmonic).sin()
+                })
+                .sum::<f64>() as f32
+        })
+        .collect()
+}
@@ -1,19 +1,122 @@
 use std::f32::consts::PI;
 
-/// Blackman-Harris窓関数を適用
-pub fn apply_blackman_harris_window(samples: &mut [f32]) {
-    let n = samples.len() as f32;
-    let a0 = 0.35875;
-    let a1 = 0.48829;
-    let a2 = 0.14128;
-    let a3 = 0.01168;
-
-    for (i, v) in samples.iter_mut().enumerate() {
-        let x = i as f32;
-        let w = a0 - a1 * (2.0 * PI * x / n).cos() + a2 * (4.0 * PI * x / n).cos()
-            - a3 * (6.0 * PI * x / n).cos();
-        *v *= w;
+/// 窓関数の種類
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WindowKind {
+    BlackmanHarris,
+    Hann,
+    Hamming,
+    /// Kaiser窓（β: 大きいほどサイドローブが低く、メインローブが広い）
+    Kaiser(f32),
+    /// フラットトップ窓（振幅の測定誤差が小さい、メインローブは最も広い）
+    FlatTop,
+}
+
+impl WindowKind {
+    /// 数値から変換（範囲外はBlackman-Harris、betaはKaiser窓のみ使用）
+    pub fn from_u32(value: u32, beta: f32) -> Self {
+        match value {
+            1 => Self::Hann,
+            2 => Self::Hamming,
+            3 => Self::Kaiser(beta),
+            4 => Self::FlatTop,
+            _ => Self::BlackmanHarris,
+        }
+    }
+
+    /// 設定ファイル用の名前から変換
+    pub fn from_name(name: &str, beta: f32) -> Option<Self> {
+        match name {
+            "blackman_harris" => Some(Self::BlackmanHarris),
+            "hann" => Some(Self::Hann),
+            "hamming" => Some(Self::Hamming),
+            "kaiser" => Some(Self::Kaiser(beta)),
+            "flat_top" => Some(Self::FlatTop),
+            _ => None,
+        }
+    }
+
+    /// 数値表現 (0=Blackman-Harris, 1=Hann, 2=Hamming, 3=Kaiser, 4=flat-top)
+    pub fn id(self) -> u32 {
+        match self {
+            Self::BlackmanHarris => 0,
+            Self::Hann => 1,
+            Self::Hamming => 2,
+            Self::Kaiser(_) => 3,
+            Self::FlatTop => 4,
+        }
     }
+
+    /// 設定ファイル用の名前
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::BlackmanHarris => "blackman_harris",
+            Self::Hann => "hann",
+            Self::Hamming => "hamming",
+            Self::Kaiser(_) => "kaiser",
+            Self::FlatTop => "flat_top",
+        }
+    }
+
+    /// 窓関数の係数表を作成（フレームごとに再計算しないよう呼び出し側でキャッシュする）
+    pub fn table(self, len: usize) -> Vec<f32> {
+        match self {
+            Self::BlackmanHarris => cosine_window(len, &[0.35875, 0.48829, 0.14128, 0.01168]),
+            Self::Hann => cosine_window(len, &[0.5, 0.5]),
+            Self::Hamming => cosine_window(len, &[0.54, 0.46]),
+            Self::Kaiser(beta) => kaiser_window(len, beta),
+            Self::FlatTop => cosine_window(
+                len,
+                &[0.21557895, 0.41663158, 0.27726316, 0.083578947, 0.006947368],
+            ),
+        }
+    }
+}
+
+/// 余弦和の窓関数（係数は a0 - a1*cos + a2*cos2 - ... の順、周期的な定義）
+fn cosine_window(len: usize, coefficients: &[f32]) -> Vec<f32> {
+    let n = len as f32;
+    (0..len)
+        .map(|i| {
+            let x = 2.0 * PI * i as f32 / n;
+            coefficients
+                .iter()
+                .enumerate()
+                .map(|(k, a)| {
+                    let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+                    sign * a * (k as f32 * x).cos()
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// Kaiser窓（周期的な定義）
+fn kaiser_window(len: usize, beta: f32) -> Vec<f32> {
+    let n = len as f64;
+    let beta = beta as f64;
+    let denom = bessel_i0(beta);
+    (0..len)
+        .map(|i| {
+            let r = 2.0 * i as f64 / n - 1.0;
+            (bessel_i0(beta * (1.0 - r * r).max(0.0).sqrt()) / denom) as f32
+        })
+        .collect()
+}
+
+/// 第1種変形ベッセル関数 I0（級数展開）
+fn bessel_i0(x: f64) -> f64 {
+    let half = x / 2.0;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for k in 1..50 {
+        term *= (half / k as f64) * (half / k as f64);
+        sum += term;
+        if term < sum * 1e-16 {
+            break;
+        }
+    }
+    sum
 }
 
 #[cfg(test)]
@@ -22,11 +125,43 @@ mod tests {
 
     #[test]
     fn test_blackman_harris_window() {
-        let mut samples = vec![1.0; 100];
-        apply_blackman_harris_window(&mut samples);
+        let window = WindowKind::BlackmanHarris.table(100);
 
         // 窓関数は両端で小さく、中央で大きくなる
-        assert!(samples[0] < samples[50]);
-        assert!(samples[99] < samples[50]);
+        assert_eq!(window.len(), 100);
+        assert!(window[0] < window[50]);
+        assert!(window[99] < window[50]);
+    }
+
+    #[test]
+    fn test_window_tables_peak_at_center() {
+        for kind in [
+            WindowKind::BlackmanHarris,
+            WindowKind::Hann,
+            WindowKind::Hamming,
+            WindowKind::Kaiser(8.0),
+            WindowKind::FlatTop,
+        ] {
+            let window = kind.table(64);
+            assert!(
+                (window[32] - 1.0).abs() < 1e-3,
+                "{}: {}",
+                kind.name(),
+                window[32]
+            );
+            assert!(window[0] < 0.1, "{}: {}", kind.name(), window[0]);
+            // 中央に対して対称
+            for i in 1..32 {
+                assert!((window[32 - i] - window[32 + i]).abs() < 1e-5);
+            }
+            assert_eq!(WindowKind::from_name(kind.name(), 8.0), Some(kind));
+            assert_eq!(WindowKind::from_u32(kind.id(), 8.0), kind);
+        }
+    }
+
+    #[test]
+    fn test_bessel_i0() {
+        assert!((bessel_i0(0.0) - 1.0).abs() < 1e-12);
+        assert!((bessel_i0(1.0) - 1.2660658777520082).abs() < 1e-12);
     }
 }
@@ -0,0 +1,150 @@
+use crate::dsp::detector::{parabolic_offset, PitchDetector, PitchEstimate, SearchRange};
+
+/// YINの閾値（累積平均正規化差分関数がこれを下回る最初の谷を周期とする）
+const YIN_THRESHOLD: f32 = 0.15;
+
+/// 差分関数の積分窓の最大長（サンプル数）
+const YIN_MAX_WINDOW: usize = 4096;
+
+/// 時間領域のYINアルゴリズムによる検出器
+pub struct YinDetector {
+    buffers: YinBuffers,
+}
+
+impl YinDetector {
+    pub fn new() -> Self {
+        Self {
+            buffers: YinBuffers::default(),
+        }
+    }
+}
+
+/// 差分関数の作業領域（フレームごとに確保しないよう検出器が保持して再利用する）
+#[derive(Default)]
+pub struct YinBuffers {
+    diff: Vec<f32>,
+    cmndf: Vec<f32>,
+}
+
+impl PitchDetector for YinDetector {
+    fn detect(
+        &mut self,
+        samples: &[f32],
+        sample_rate: usize,
+        range: &SearchRange,
+    ) -> Option<PitchEstimate> {
+        let (freq, clarity) = estimate_yin(
+            samples,
+            sample_rate,
+            range.min_freq,
+            range.max_freq,
+            &mut self.buffers,
+        )?;
+        Some(PitchEstimate {
+            raw_frequency: freq,
+            fundamental: Some(freq),
+            confidence: clarity,
+            peak_to_noise: None,
+            fft_size: None,
+        })
+    }
+}
+
+/// YINで基本周波数を推定
+/// 戻り値: (周波数, 明瞭度 0.0〜1.0) - 周期性が見つからなければNone
+pub fn estimate_yin(
+    samples: &[f32],
+    sample_rate: usize,
+    min_freq: f32,
+    max_freq: f32,
+    buffers: &mut YinBuffers,
+) -> Option<(f32, f32)> {
+    let tau_min = ((sample_rate as f32 / max_freq) as usize).max(2);
+    let tau_max = (sample_rate as f32 / min_freq).ceil() as usize;
+    if samples.len() < tau_max + 3 {
+        return None;
+    }
+
+    // 最新のサンプルを使う（積分窓 + 最大ラグ分）
+    let window = (samples.len() - tau_max - 2).min(YIN_MAX_WINDOW);
+    let x = &samples[samples.len() - (window + tau_max + 2)..];
+
+    // 差分関数 d(tau)
+    let diff = &mut buffers.diff;
+    diff.clear();
+    diff.resize(tau_max + 2, 0.0);
+    for (tau, d) in diff.iter_mut().enumerate().skip(1) {
+        *d = x[..window]
+            .iter()
+            .zip(&x[tau..tau + window])
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum();
+    }
+
+    // 累積平均正規化差分関数 d'(tau)
+    let cmndf = &mut buffers.cmndf;
+    cmndf.clear();
+    cmndf.resize(tau_max + 2, 1.0);
+    let mut running_sum = 0.0f32;
+    for tau in 1..tau_max + 2 {
+        running_sum += diff[tau];
+        cmndf[tau] = if running_sum > 0.0 {
+            diff[tau] * tau as f32 / running_sum
+        } else {
+            1.0
+        };
+    }
+
+    // 閾値を下回る最初の谷を探す
+    let mut tau = (tau_min..=tau_max).find(|&t| cmndf[t] < YIN_THRESHOLD)?;
+    while tau < tau_max && cmndf[tau + 1] < cmndf[tau] {
+        tau += 1;
+    }
+
+    // 差分関数上で放物線補間して周期を精密化
+    let offset = parabolic_offset(diff[tau - 1], diff[tau], diff[tau + 1]);
+    let period = tau as f32 + offset;
+    if period <= 0.0 {
+        return None;
+    }
+
+    Some((
+        sample_rate as f32 / period,
+        (1.0 - cmndf[tau]).clamp(0.0, 1.0),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::test_signals::sine;
+
+    #[test]
+    fn test_estimate_yin_sine() {
+        let samples = sine(110.0, 48000, 8192);
+        let (freq, clarity) =
+            estimate_yin(&samples, 48000, 75.0, 350.0, &mut YinBuffers::default()).unwrap();
+        let cents = 1200.0 * (freq / 110.0).log2();
+        assert!(cents.abs() < 1.0, "cents = {}", cents);
+        assert!(clarity > 0.9);
+    }
+
+    #[test]
+    fn test_estimate_yin_prefers_fundamental() {
+        // 第2倍音が基音より強いE2
+        let samples: Vec<f32> = sine(82.41, 48000, 8192)
+            .iter()
+            .zip(sine(164.82, 48000, 8192))
+            .map(|(a, b)| 0.4 * a + b)
+            .collect();
+        let (freq, _) =
+            estimate_yin(&samples, 48000, 75.0, 350.0, &mut YinBuffers::default()).unwrap();
+        assert!((freq - 82.41).abs() < 1.0, "freq = {}", freq);
+    }
+
+    #[test]
+    fn test_estimate_yin_silence() {
+        let samples = vec![0.0; 8192];
+        assert!(estimate_yin(&samples, 48000, 75.0, 350.0, &mut YinBuffers::default()).is_none());
+    }
+}
@@ -0,0 +1,193 @@
+use serde::Serialize;
+
+use crate::tuning::{find_tuning, standard_tuning, Tuning, STANDARD_TUNING_NAME};
+
+/// 楽器の種類（弦の数・標準チューニング・解析範囲を決める）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instrument {
+    Guitar = 0,
+    SevenString = 1,
+    EightString = 2,
+    Baritone = 3,
+    Ukulele = 4,
+    Mandolin = 5,
+    Violin = 6,
+    Bass = 7,
+    FiveStringBass = 8,
+    SixStringBass = 9,
+}
+
+/// フロントエンド向けの楽器情報
+#[derive(Debug, Serialize, Clone)]
+pub struct InstrumentInfo {
+    pub id: u32,
+    pub name: &'static str,
+    pub string_count: usize,
+    pub default_tuning: Tuning,
+    pub min_freq: f32,
+    pub max_freq: f32,
+    pub tolerance: f32,
+}
+
+impl Instrument {
+    /// すべての楽器
+    pub const ALL: [Instrument; 10] = [
+        Self::Guitar,
+        Self::SevenString,
+        Self::EightString,
+        Self::Baritone,
+        Self::Ukulele,
+        Self::Mandolin,
+        Self::Violin,
+        Self::Bass,
+        Self::FiveStringBass,
+        Self::SixStringBass,
+    ];
+
+    /// 数値から変換（範囲外はギター）
+    pub fn from_u32(value: u32) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|i| *i as u32 == value)
+            .unwrap_or(Self::Guitar)
+    }
+
+    /// 設定ファイル用の名前から変換
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|i| i.name() == name)
+    }
+
+    /// 設定ファイル用の名前
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Guitar => "guitar",
+            Self::SevenString => "guitar7",
+            Self::EightString => "guitar8",
+            Self::Baritone => "baritone",
+            Self::Ukulele => "ukulele",
+            Self::Mandolin => "mandolin",
+            Self::Violin => "violin",
+            Self::Bass => "bass",
+            Self::FiveStringBass => "bass5",
+            Self::SixStringBass => "bass6",
+        }
+    }
+
+    /// 弦の数
+    pub fn string_count(self) -> usize {
+        match self {
+            Self::Guitar | Self::Baritone | Self::SixStringBass => 6,
+            Self::SevenString => 7,
+            Self::EightString => 8,
+            Self::Ukulele | Self::Mandolin | Self::Violin | Self::Bass => 4,
+            Self::FiveStringBass => 5,
+        }
+    }
+
+    /// 標準チューニングの名前（組み込みプリセット）
+    pub fn default_tuning_name(self) -> &'static str {
+        match self {
+            Self::Guitar => STANDARD_TUNING_NAME,
+            Self::SevenString => "7-String Standard",
+            Self::EightString => "8-String Standard",
+            Self::Baritone => "Baritone Standard",
+            Self::Ukulele => "Ukulele Standard",
+            Self::Mandolin => "Mandolin Standard",
+            Self::Violin => "Violin Standard",
+            Self::Bass => "Bass Standard",
+            Self::FiveStringBass => "5-String Bass Standard",
+            Self::SixStringBass => "6-String Bass Standard",
+        }
+    }
+
+    /// 標準チューニング
+    pub fn default_tuning(self) -> Tuning {
+        find_tuning(self.default_tuning_name(), &[]).unwrap_or_else(standard_tuning)
+    }
+
+    /// 周波数解析範囲（Hz）
+    pub fn frequency_range(self) -> (f32, f32) {
+        match self {
+            Self::Guitar => (75.0, 350.0),
+            Self::SevenString => (55.0, 350.0),
+            Self::EightString => (40.0, 350.0),
+            Self::Baritone => (55.0, 300.0),
+            Self::Ukulele => (230.0, 500.0),
+            Self::Mandolin | Self::Violin => (170.0, 750.0),
+            Self::Bass => (35.0, 120.0),
+            Self::FiveStringBass => (28.0, 120.0),
+            Self::SixStringBass => (28.0, 150.0),
+        }
+    }
+
+    /// 弦の目標周波数に対する許容誤差（比率）
+    /// 隣り合う弦の音程が狭い楽器ほど小さくする
+    pub fn tolerance(self) -> f32 {
+        match self {
+            Self::Ukulele => 0.1,
+            _ => 0.15,
+        }
+    }
+
+    /// 低音域用の解析経路（間引き + 時間領域の検出）を使うか
+    /// FFT_SIZEのbin幅ではB0〜E1付近で数十セントの誤差になるため
+    pub fn uses_low_frequency_path(self) -> bool {
+        matches!(
+            self,
+            Self::Bass | Self::FiveStringBass | Self::SixStringBass
+        )
+    }
+
+    /// 最低音弦のドロップチューニングに対応するか（ドロップ音はギターの6弦基準）
+    pub fn supports_drop_tuning(self) -> bool {
+        self == Self::Guitar
+    }
+
+    /// フロントエンド向けの楽器情報
+    pub fn info(self) -> InstrumentInfo {
+        let (min_freq, max_freq) = self.frequency_range();
+        InstrumentInfo {
+            id: self as u32,
+            name: self.name(),
+            string_count: self.string_count(),
+            default_tuning: self.default_tuning(),
+            min_freq,
+            max_freq,
+            tolerance: self.tolerance(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_tunings_match_instruments() {
+        for instrument in Instrument::ALL {
+            let tuning = instrument.default_tuning();
+            assert_eq!(tuning.name, instrument.default_tuning_name());
+            assert_eq!(tuning.strings.len(), instrument.string_count());
+
+            // すべての弦が解析範囲内に収まる
+            let (min_freq, max_freq) = instrument.frequency_range();
+            for freq in tuning.frequencies() {
+                assert!(
+                    (min_freq..=max_freq).contains(&freq),
+                    "{}: {} Hz",
+                    instrument.name(),
+                    freq
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_instrument_name_round_trip() {
+        for instrument in Instrument::ALL {
+            assert_eq!(Instrument::from_name(instrument.name()), Some(instrument));
+            assert_eq!(Instrument::from_u32(instrument as u32), instrument);
+        }
+        assert_eq!(Instrument::from_u32(99), Instrument::Guitar);
+    }
+}
@@ -2,12 +2,26 @@ mod audio;
 mod commands;
 mod constants;
 mod dsp;
+mod instrument;
+mod pitch;
+mod temperament;
+mod tuning;
 
 use commands::{
-    get_audio_devices, get_channel_mode, get_locale, get_settings, get_threshold,
-    get_tray_icon_mode, set_always_on_top, set_channel_mode, set_custom_pitch, set_drop_tuning,
-    set_locale, set_pitch_mode, set_settings, set_threshold, set_tray_icon_mode, set_tuning_shift,
-    start_listening,
+    calibrate_noise, create_offset_table, create_tuning, delete_offset_table, delete_tuning,
+    get_audio_devices, get_capo, get_channel_mode, get_hop_size, get_hum_filter, get_instrument,
+    get_instruments, get_kaiser_beta, get_last_detection, get_locale, get_mains_frequency,
+    get_noise_gate, get_noise_reduction, get_offset_table, get_offset_tables, get_onset_skip,
+    get_peak_interpolator, get_peak_refinement, get_pitch_detector, get_pitch_presets,
+    get_pitch_reference, get_pitch_smoothing, get_settings, get_smoothing_parameters,
+    get_stream_state, get_temperament, get_threshold, get_tray_icon_mode, get_tuner_mode,
+    get_tuning, get_tunings, get_window_function, load_scala_file, set_always_on_top, set_capo,
+    set_channel_mode, set_custom_pitch, set_drop_tuning, set_hop_size, set_hum_filter,
+    set_instrument, set_locale, set_noise_reduction, set_offset_table, set_onset_skip,
+    set_peak_interpolator, set_peak_refinement, set_pitch_detector, set_pitch_mode,
+    set_pitch_preset, set_pitch_smoothing, set_settings, set_temperament, set_threshold,
+    set_tray_icon_mode, set_tuner_mode, set_tuning, set_tuning_shift, set_tuning_shift_cents,
+    set_window_function, start_listening,
 };
 
 pub fn run() {
@@ -24,6 +38,10 @@ pub fn run() {
             start_listening,
             set_threshold,
             get_threshold,
+            calibrate_noise,
+            get_noise_gate,
+            set_noise_reduction,
+            get_noise_reduction,
             set_channel_mode,
             get_channel_mode,
             set_tray_icon_mode,
@@ -34,6 +52,51 @@ pub fn run() {
             set_custom_pitch,
             set_tuning_shift,
             set_drop_tuning,
+            set_tuner_mode,
+            get_tuner_mode,
+            set_instrument,
+            get_instrument,
+            get_instruments,
+            get_tunings,
+            set_tuning,
+            get_tuning,
+            create_tuning,
+            delete_tuning,
+            get_offset_tables,
+            set_offset_table,
+            get_offset_table,
+            create_offset_table,
+            delete_offset_table,
+            set_temperament,
+            load_scala_file,
+            get_temperament,
+            set_pitch_detector,
+            get_pitch_detector,
+            set_peak_refinement,
+            get_peak_refinement,
+            set_hum_filter,
+            get_hum_filter,
+            get_mains_frequency,
+            set_onset_skip,
+            get_onset_skip,
+            set_pitch_smoothing,
+            get_pitch_smoothing,
+            get_smoothing_parameters,
+            set_window_function,
+            get_window_function,
+            get_kaiser_beta,
+            set_peak_interpolator,
+            get_peak_interpolator,
+            set_hop_size,
+            get_hop_size,
+            get_stream_state,
+            get_last_detection,
+            set_tuning_shift_cents,
+            get_pitch_presets,
+            get_pitch_reference,
+            set_capo,
+            get_capo,
+            set_pitch_preset,
             set_always_on_top,
             set_locale,
             get_locale
@@ -41,10 +104,13 @@ pub fn run() {
         .setup(|app| {
             let app_handle = app.handle().clone();
 
-            // Load locale from settings
-            use crate::commands::{get_settings, get_tray_menu_text};
+            // Load locale and analysis settings from settings
+            use crate::commands::{apply_analysis_settings, get_settings, get_tray_menu_text};
             let locale = match get_settings() {
-                Ok(settings) => settings.locale.unwrap_or_else(|| "en".to_string()),
+                Ok(settings) => {
+                    apply_analysis_settings(&settings);
+                    settings.locale.unwrap_or_else(|| "en".to_string())
+                }
                 Err(_) => "en".to_string(),
             };
             *constants::LOCALE.write().unwrap() = locale.clone();
@@ -0,0 +1,463 @@
+use serde::Serialize;
+use std::sync::atomic::Ordering;
+
+use crate::constants::{ACTIVE_OFFSET_TABLE, CAPO, CUSTOM_PITCH, PITCH_MODE, TUNING_SHIFT_CENTS};
+use crate::temperament::Temperament;
+use crate::tuning::{OffsetTable, StringPitch};
+
+/// 標準の基準ピッチ（Hz）
+pub const STANDARD_A4: f32 = 440.0;
+
+/// カスタム基準ピッチの範囲（Hz）と刻み
+pub const MIN_CUSTOM_PITCH: f32 = 400.0;
+pub const MAX_CUSTOM_PITCH: f32 = 480.0;
+pub const CUSTOM_PITCH_STEP: f32 = 0.1;
+
+/// 基準ピッチのシフトの上限（セント、上下とも）
+pub const MAX_TUNING_SHIFT_CENTS: f32 = 1200.0;
+
+/// カポの最大フレット
+pub const MAX_CAPO_FRET: u32 = 12;
+
+/// 基準ピッチの指定方法
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PitchMode {
+    /// A4 = 440Hz
+    Standard = 0,
+    /// 任意のA4（音名はそのまま、周波数だけが変わる）
+    Custom = 1,
+    /// A4=440Hzからのシフト（半音下げチューニングなど、目標の音名も変わる）
+    Shift = 2,
+}
+
+impl PitchMode {
+    /// 数値から変換（範囲外は標準）
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => Self::Custom,
+            2 => Self::Shift,
+            _ => Self::Standard,
+        }
+    }
+
+    /// 設定ファイル用の名前から変換
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "standard" => Some(Self::Standard),
+            "custom" => Some(Self::Custom),
+            "shift" => Some(Self::Shift),
+            _ => None,
+        }
+    }
+
+    /// 設定ファイル用の名前
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Custom => "custom",
+            Self::Shift => "shift",
+        }
+    }
+}
+
+/// 名前付きの基準ピッチ
+#[derive(Debug, Serialize, Clone, Copy)]
+pub struct PitchPreset {
+    /// 識別子（set_pitch_preset とフロントエンドの翻訳キーに使う）
+    pub id: &'static str,
+    /// 表示名（英語）
+    pub name: &'static str,
+    pub a4: f32,
+}
+
+/// 基準ピッチのプリセット（古楽・オーケストラなどで使われる高さ）
+pub const PITCH_PRESETS: [PitchPreset; 7] = [
+    PitchPreset {
+        id: "a415",
+        name: "A415 (Baroque)",
+        a4: 415.0,
+    },
+    PitchPreset {
+        id: "a430",
+        name: "A430 (Classical)",
+        a4: 430.0,
+    },
+    PitchPreset {
+        id: "a432",
+        name: "A432",
+        a4: 432.0,
+    },
+    PitchPreset {
+        id: "a440",
+        name: "A440 (Standard)",
+        a4: 440.0,
+    },
+    PitchPreset {
+        id: "a442",
+        name: "A442 (Orchestra)",
+        a4: 442.0,
+    },
+    PitchPreset {
+        id: "a443",
+        name: "A443 (Orchestra)",
+        a4: 443.0,
+    },
+    PitchPreset {
+        id: "a466",
+        name: "A466 (Chorton)",
+        a4: 466.0,
+    },
+];
+
+/// カスタム基準ピッチを検証して0.1Hz刻みに丸める
+pub fn normalize_custom_pitch(pitch: f32) -> Result<f32, String> {
+    if !(MIN_CUSTOM_PITCH..=MAX_CUSTOM_PITCH).contains(&pitch) {
+        return Err(format!(
+            "Pitch must be between {} and {} Hz",
+            MIN_CUSTOM_PITCH, MAX_CUSTOM_PITCH
+        ));
+    }
+    Ok((pitch / CUSTOM_PITCH_STEP).round() * CUSTOM_PITCH_STEP)
+}
+
+/// 基準ピッチのシフト（セント）を検証
+pub fn validate_tuning_shift(cents: f32) -> Result<f32, String> {
+    if !(cents.is_finite() && cents.abs() <= MAX_TUNING_SHIFT_CENTS) {
+        return Err(format!(
+            "Tuning shift must be within ±{} cents",
+            MAX_TUNING_SHIFT_CENTS
+        ));
+    }
+    Ok(cents)
+}
+
+/// カポの位置を検証
+pub fn validate_capo(fret: u32) -> Result<u32, String> {
+    if fret > MAX_CAPO_FRET {
+        return Err(format!("Capo fret must be between 0 and {}", MAX_CAPO_FRET));
+    }
+    Ok(fret)
+}
+
+/// 目標周波数の計算に使うA4（custom・shiftとも反映）
+pub fn effective_a4(mode: PitchMode, custom_pitch: f32, shift_cents: f32) -> f32 {
+    match mode {
+        PitchMode::Standard => STANDARD_A4,
+        PitchMode::Custom => custom_pitch,
+        PitchMode::Shift => STANDARD_A4 * (shift_cents / 1200.0).exp2(),
+    }
+}
+
+/// 音名・目標・セント値の計算の基準（A4・シフト・カポ・弦ごとのオフセット）
+/// シフトは半音単位の部分を目標の移調（音名が変わる）、半音未満の部分を音名の基準A4のずれとして扱う
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PitchReference {
+    pub mode: PitchMode,
+    /// カスタム基準ピッチ（customモードのみ使う）
+    pub custom_pitch: f32,
+    /// A4=440Hzからのシフト（セント、shiftモードのみ使う）
+    pub shift_cents: f32,
+    /// カポの位置（フレット、0=なし）
+    pub capo: u32,
+    /// 弦ごとのオフセット表（Noneならオフセットなし）
+    pub offset_table: Option<OffsetTable>,
+}
+
+impl Default for PitchReference {
+    fn default() -> Self {
+        Self {
+            mode: PitchMode::Standard,
+            custom_pitch: STANDARD_A4,
+            shift_cents: 0.0,
+            capo: 0,
+            offset_table: None,
+        }
+    }
+}
+
+impl PitchReference {
+    /// 現在の設定から作成
+    pub fn current() -> Self {
+        Self {
+            mode: PitchMode::from_u32(PITCH_MODE.load(Ordering::SeqCst)),
+            custom_pitch: *CUSTOM_PITCH.read().unwrap(),
+            shift_cents: *TUNING_SHIFT_CENTS.read().unwrap(),
+            capo: CAPO.load(Ordering::SeqCst),
+            offset_table: ACTIVE_OFFSET_TABLE.read().unwrap().clone(),
+        }
+    }
+
+    /// シフトの半音単位の部分
+    fn shift_semitones(&self) -> i32 {
+        match self.mode {
+            PitchMode::Shift => (self.shift_cents / 100.0).round() as i32,
+            PitchMode::Standard | PitchMode::Custom => 0,
+        }
+    }
+
+    /// 目標周波数の基準のA4（custom・shiftとも反映、カポは含まない）
+    pub fn target_a4(&self) -> f32 {
+        effective_a4(self.mode, self.custom_pitch, self.shift_cents)
+    }
+
+    /// 音名の基準のA4（customとshiftの半音未満の部分を反映）
+    pub fn note_a4(&self) -> f32 {
+        match self.mode {
+            PitchMode::Standard => STANDARD_A4,
+            PitchMode::Custom => self.custom_pitch,
+            PitchMode::Shift => {
+                let fraction = self.shift_cents - self.shift_semitones() as f32 * 100.0;
+                STANDARD_A4 * (fraction / 1200.0).exp2()
+            }
+        }
+    }
+
+    /// 目標の移調量（半音、シフトの半音単位の部分とカポ）
+    pub fn transposition(&self) -> i32 {
+        self.shift_semitones() + self.capo as i32
+    }
+
+    /// A4=440Hz基準の周波数を移調して音名の基準A4に合わせる
+    pub fn transpose(&self, freq: f32) -> f32 {
+        freq * self.note_a4() / STANDARD_A4 * (self.transposition() as f32 / 12.0).exp2()
+    }
+
+    /// 弦のオフセット（セント、表にない弦は0）
+    pub fn string_offset(&self, string_index: usize) -> f32 {
+        self.offset_table
+            .as_ref()
+            .map_or(0.0, |table| table.offset(string_index))
+    }
+
+    /// 移調した目標に弦のオフセットを加える
+    pub fn apply_offset(&self, freq: f32, string_index: usize) -> f32 {
+        freq * (self.string_offset(string_index) / 1200.0).exp2()
+    }
+}
+
+/// 弦の目標（表示する音名と、オフセットを加えた目標周波数）
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct StringTarget {
+    /// チューニング上の弦の位置（0始まり、解釈できない弦を除外しても元の並びの番号）
+    pub index: usize,
+    pub name: String,
+    pub frequency: f32,
+}
+
+impl PitchReference {
+    /// 各弦の目標を求める（解釈できない弦は除外）
+    /// 音名指定の弦は移調してから音律の最も近い音に合わせ、周波数指定の弦は移調だけする
+    pub fn string_targets(
+        &self,
+        strings: &[StringPitch],
+        temperament: &Temperament,
+    ) -> Vec<StringTarget> {
+        let note_a4 = self.note_a4();
+        // オフセット表は弦の位置に対応するので、除外する前に番号を付ける
+        strings
+            .iter()
+            .enumerate()
+            .filter_map(|(i, string)| {
+                let transposed = self.transpose(string.frequency()?);
+                let (name, tempered, _) = temperament.nearest_note(transposed, note_a4);
+                let frequency = match string {
+                    StringPitch::Note(_) => tempered,
+                    StringPitch::Frequency(_) => transposed,
+                };
+                Some(StringTarget {
+                    index: i,
+                    name,
+                    frequency: self.apply_offset(frequency, i),
+                })
+            })
+            .collect()
+    }
+}
+
+/// 周波数に最も近い目標（弦）のインデックス
+pub fn nearest_target_index(freq: f32, targets: &[f32]) -> Option<usize> {
+    targets
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| {
+            let diff_a = (freq - *a).abs();
+            let diff_b = (freq - *b).abs();
+            diff_a.partial_cmp(&diff_b).unwrap()
+        })
+        .map(|(i, _)| i)
+}
+
+/// 目標周波数に対するずれ（セント）
+pub fn cents_from(freq: f32, target: f32) -> f32 {
+    1200.0 * (freq / target).log2()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 0.01,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_effective_a4() {
+        assert_eq!(effective_a4(PitchMode::Standard, 415.0, -100.0), 440.0);
+        assert_eq!(effective_a4(PitchMode::Custom, 442.5, -100.0), 442.5);
+        // 半音下げ・全音下げ・半音上げ
+        assert_close(effective_a4(PitchMode::Shift, 442.0, -100.0), 415.30);
+        assert_close(effective_a4(PitchMode::Shift, 442.0, -200.0), 392.00);
+        assert_close(effective_a4(PitchMode::Shift, 442.0, 100.0), 466.16);
+        // 半音未満のシフト
+        assert_close(effective_a4(PitchMode::Shift, 442.0, -50.0), 427.47);
+        assert_close(effective_a4(PitchMode::Shift, 442.0, 7.85), 442.0);
+    }
+
+    fn reference(mode: PitchMode, custom_pitch: f32, shift_cents: f32) -> PitchReference {
+        PitchReference {
+            mode,
+            custom_pitch,
+            shift_cents,
+            ..PitchReference::default()
+        }
+    }
+
+    #[test]
+    fn test_custom_a4_for_note() {
+        assert_eq!(reference(PitchMode::Standard, 415.0, 0.0).note_a4(), 440.0);
+        assert_eq!(reference(PitchMode::Custom, 415.0, 0.0).note_a4(), 415.0);
+        // 半音単位のシフトは音名の基準を変えずに移調する
+        assert_eq!(reference(PitchMode::Shift, 415.0, -200.0).note_a4(), 440.0);
+        // 半音未満の部分だけ音名の基準をずらす
+        assert_close(reference(PitchMode::Shift, 415.0, -130.0).note_a4(), 432.45);
+        assert_close(reference(PitchMode::Shift, 415.0, -50.0).note_a4(), 452.89);
+        for preset in PITCH_PRESETS {
+            let reference = reference(PitchMode::Custom, preset.a4, 0.0);
+            assert_eq!(reference.note_a4(), preset.a4);
+            assert_eq!(reference.target_a4(), preset.a4);
+        }
+    }
+
+    #[test]
+    fn test_pitch_reference_targets() {
+        let e2 = 82.407;
+        // 移調した目標は常にtarget_a4に比例する
+        for shift in [-250.0, -100.0, -50.0, -30.0, 0.0, 30.0, 100.0] {
+            let reference = reference(PitchMode::Shift, 440.0, shift);
+            let ratio = reference.target_a4() / STANDARD_A4;
+            assert_close(reference.transpose(e2), e2 * ratio);
+        }
+        // 半音下げはD#2、カポ2フレットはF#2
+        let half_step_down = reference(PitchMode::Shift, 440.0, -100.0);
+        assert_eq!(half_step_down.transposition(), -1);
+        assert_close(half_step_down.transpose(e2), 77.78);
+        let capo = PitchReference {
+            capo: 2,
+            ..PitchReference::default()
+        };
+        assert_close(capo.transpose(e2), 92.50);
+        // customモードは移調しない
+        assert_close(
+            reference(PitchMode::Custom, 415.0, -100.0).transpose(e2),
+            77.73,
+        );
+
+        // 弦ごとのオフセットは目標にだけ加える
+        let offsets = PitchReference {
+            offset_table: Some(OffsetTable::new("Test", &[-2.0, 0.0])),
+            ..PitchReference::default()
+        };
+        assert_close(cents_from(offsets.apply_offset(e2, 0), e2), -2.0);
+        assert_eq!(offsets.string_offset(5), 0.0);
+    }
+
+    #[test]
+    fn test_string_targets_follow_reference() {
+        let strings = vec![
+            StringPitch::Note("E2".to_string()),
+            StringPitch::Frequency(110.5),
+            StringPitch::Note("D3".to_string()),
+        ];
+        let equal = Temperament::default();
+
+        // 半音下げ: 音名も目標も半音下がり、セント値は表示する目標に対する値になる
+        let half_step_down = PitchReference {
+            offset_table: Some(OffsetTable::new("Test", &[0.0, 0.0, -3.0])),
+            ..reference(PitchMode::Shift, 440.0, -100.0)
+        };
+        let targets = half_step_down.string_targets(&strings, &equal);
+        let names: Vec<&str> = targets.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["D#2", "G#2", "C#3"]);
+        assert_close(targets[0].frequency, 77.78);
+        // 周波数指定の弦は音律に合わせない
+        assert_close(targets[1].frequency, 110.5 * 0.943_874_3);
+        // オフセットは目標に含まれる
+        assert_close(cents_from(targets[2].frequency, 138.591), -3.0);
+
+        // 半音未満のシフトは音名を変えずに目標だけをずらす
+        let quarter = reference(PitchMode::Shift, 440.0, -30.0);
+        let targets = quarter.string_targets(&strings, &equal);
+        assert_eq!(targets[0].name, "E2");
+        assert_close(cents_from(targets[0].frequency, 82.407), -30.0);
+
+        // カポは音名指定・周波数指定の弦とも移調し、半音下げと組み合わせられる
+        let capo = PitchReference {
+            capo: 3,
+            ..reference(PitchMode::Shift, 440.0, -100.0)
+        };
+        let targets = capo.string_targets(&strings, &equal);
+        let names: Vec<&str> = targets.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["F#2", "B2", "E3"]);
+        assert_close(targets[0].frequency, 92.50);
+        assert_close(cents_from(targets[1].frequency, 110.5), 200.0);
+
+        // 解釈できない弦を除外しても、オフセットは元の弦の位置のものを使う
+        let skipped = vec![
+            StringPitch::Note("X9".to_string()),
+            StringPitch::Note("A2".to_string()),
+            StringPitch::Note("D3".to_string()),
+        ];
+        let offsets = PitchReference {
+            offset_table: Some(OffsetTable::new("Test", &[-5.0, 2.0, -3.0])),
+            ..PitchReference::default()
+        };
+        let targets = offsets.string_targets(&skipped, &equal);
+        let names: Vec<&str> = targets.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["A2", "D3"]);
+        let indices: Vec<usize> = targets.iter().map(|t| t.index).collect();
+        assert_eq!(indices, [1, 2]);
+        assert_close(cents_from(targets[0].frequency, 110.0), 2.0);
+        assert_close(cents_from(targets[1].frequency, 146.832), -3.0);
+
+        assert_eq!(nearest_target_index(81.0, &[81.0, 109.0]), Some(0));
+        assert_eq!(nearest_target_index(81.0, &[]), None);
+    }
+
+    #[test]
+    fn test_pitch_validation() {
+        assert_close(normalize_custom_pitch(442.04).unwrap(), 442.0);
+        assert_close(normalize_custom_pitch(415.37).unwrap(), 415.4);
+        assert!(normalize_custom_pitch(400.0).is_ok());
+        assert!(normalize_custom_pitch(480.0).is_ok());
+        assert!(normalize_custom_pitch(399.9).is_err());
+        assert!(normalize_custom_pitch(f32::NAN).is_err());
+
+        assert_eq!(validate_tuning_shift(-250.0), Ok(-250.0));
+        assert!(validate_tuning_shift(1300.0).is_err());
+        assert!(validate_tuning_shift(f32::INFINITY).is_err());
+        assert_eq!(validate_capo(0), Ok(0));
+        assert_eq!(validate_capo(MAX_CAPO_FRET), Ok(12));
+        assert!(validate_capo(13).is_err());
+        assert_eq!(
+            PitchMode::from_name(PitchMode::Shift.name()),
+            Some(PitchMode::Shift)
+        );
+    }
+}
@@ -0,0 +1,580 @@
+use std::fs;
+
+use crate::tuning::NOTE_NAMES;
+
+/// 平均律の分割数の範囲
+pub const MIN_EDO_DIVISIONS: u32 = 5;
+pub const MAX_EDO_DIVISIONS: u32 = 72;
+
+/// 音名にアップ・ダウン記号（^/v）を付ける、12平均律の音からのずれ（セント）
+const MICROTONE_MARK_CENTS: f32 = 20.0;
+
+/// 5度の大きさ（セント）
+const PURE_FIFTH_CENTS: f32 = 701.955;
+/// 1/4コンマ・ミーントーンの5度（長3度が純正になるよう5度を狭めたもの）
+const QUARTER_COMMA_FIFTH_CENTS: f32 = 696.578;
+
+/// 各半音が主音から5度いくつ分か（E♭〜G♯の範囲で5度を積み重ねる）
+const FIFTHS_FROM_TONIC: [i32; 12] = [0, 7, 2, -3, 4, -1, 6, 1, 8, 3, -2, 5];
+
+/// 純正律（5リミット）の主音からの周波数比
+const JUST_RATIOS: [(u32, u32); 12] = [
+    (1, 1),
+    (16, 15),
+    (9, 8),
+    (6, 5),
+    (5, 4),
+    (4, 3),
+    (45, 32),
+    (3, 2),
+    (8, 5),
+    (5, 3),
+    (9, 5),
+    (15, 8),
+];
+
+/// ヴェルクマイスターIII（主音からのセント）
+const WERCKMEISTER_III: [f32; 12] = [
+    0.0, 90.225, 192.18, 294.135, 390.225, 498.045, 588.27, 696.09, 792.18, 888.27, 996.09, 1092.18,
+];
+
+/// 音律の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemperamentKind {
+    /// 1オクターブをn等分する平均律（12, 19, 24, 31-EDOなど）
+    Equal = 0,
+    /// 主音を基準にした純正律
+    Just = 1,
+    /// ピタゴラス音律
+    Pythagorean = 2,
+    /// 1/4コンマ・ミーントーン
+    Meantone = 3,
+    /// ヴェルクマイスターIII
+    Werckmeister = 4,
+    /// Scalaファイル（.scl/.kbm）から読み込んだ音律
+    Scala = 5,
+}
+
+impl TemperamentKind {
+    /// 数値から変換（範囲外は平均律）
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => Self::Just,
+            2 => Self::Pythagorean,
+            3 => Self::Meantone,
+            4 => Self::Werckmeister,
+            5 => Self::Scala,
+            _ => Self::Equal,
+        }
+    }
+
+    /// 設定ファイル用の名前から変換
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "equal" => Some(Self::Equal),
+            "just" => Some(Self::Just),
+            "pythagorean" => Some(Self::Pythagorean),
+            "meantone" => Some(Self::Meantone),
+            "werckmeister" => Some(Self::Werckmeister),
+            "scala" => Some(Self::Scala),
+            _ => None,
+        }
+    }
+
+    /// 設定ファイル用の名前
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Equal => "equal",
+            Self::Just => "just",
+            Self::Pythagorean => "pythagorean",
+            Self::Meantone => "meantone",
+            Self::Werckmeister => "werckmeister",
+            Self::Scala => "scala",
+        }
+    }
+}
+
+/// 鍵盤（音の番号）と音度の対応（Scalaの.kbmに相当）
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyboardMapping {
+    /// 対応表の周期（鍵盤数、0なら鍵盤を順に音度へ割り当てる）
+    pub size: usize,
+    /// 有効な鍵盤の範囲
+    pub first_note: i32,
+    pub last_note: i32,
+    /// 音度0に対応する鍵盤
+    pub middle_note: i32,
+    /// 基準周波数を与える鍵盤とその周波数（A4=440Hz基準）
+    pub reference_note: i32,
+    pub reference_freq: f32,
+    /// 対応表の1周期に相当する音度（0なら音律の周期）
+    pub octave_degree: usize,
+    /// 対応表の各鍵盤の音度（Noneは割り当てなし）
+    pub keys: Vec<Option<usize>>,
+}
+
+impl KeyboardMapping {
+    /// 鍵盤を順に音度へ割り当てる対応
+    pub fn linear(middle_note: i32, reference_note: i32, reference_freq: f32) -> Self {
+        Self {
+            size: 0,
+            first_note: i32::MIN,
+            last_note: i32::MAX,
+            middle_note,
+            reference_note,
+            reference_freq,
+            octave_degree: 0,
+            keys: Vec::new(),
+        }
+    }
+}
+
+/// 音律（1周期内の各音度の高さと、鍵盤への対応）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Temperament {
+    pub kind: TemperamentKind,
+    pub name: String,
+    /// 主音の音名のインデックス（C=0、12音の音律のみ）
+    pub key: u32,
+    /// 各音度の音度0からのセント（先頭は0）
+    pub degrees: Vec<f32>,
+    /// 周期（セント、通常は1200）
+    pub period: f32,
+    pub mapping: KeyboardMapping,
+}
+
+impl Default for Temperament {
+    fn default() -> Self {
+        Self::equal(12)
+    }
+}
+
+impl Temperament {
+    /// n平均律（A4を基準音とする）
+    pub fn equal(divisions: u32) -> Self {
+        let divisions = divisions.clamp(MIN_EDO_DIVISIONS, MAX_EDO_DIVISIONS);
+        Self {
+            kind: TemperamentKind::Equal,
+            name: format!("{}-EDO", divisions),
+            key: 0,
+            degrees: (0..divisions)
+                .map(|i| i as f32 * 1200.0 / divisions as f32)
+                .collect(),
+            period: 1200.0,
+            mapping: KeyboardMapping::linear(69, 69, 440.0),
+        }
+    }
+
+    /// 種類・分割数・主音から組み込みの音律を作成
+    /// 12音の音律は主音を基準に各音を並べ、A4を基準ピッチの高さに合わせる（主音は平均律からずれうる）
+    pub fn from_kind(kind: TemperamentKind, divisions: u32, key: u32) -> Result<Self, String> {
+        let degrees: Vec<f32> = match kind {
+            TemperamentKind::Equal => return Ok(Self::equal(divisions)),
+            TemperamentKind::Just => JUST_RATIOS
+                .iter()
+                .map(|&(n, d)| 1200.0 * (n as f32 / d as f32).log2())
+                .collect(),
+            TemperamentKind::Pythagorean => fifths_chain(PURE_FIFTH_CENTS),
+            TemperamentKind::Meantone => fifths_chain(QUARTER_COMMA_FIFTH_CENTS),
+            TemperamentKind::Werckmeister => WERCKMEISTER_III.to_vec(),
+            TemperamentKind::Scala => {
+                return Err("Scala temperaments must be loaded from a file".to_string())
+            }
+        };
+        let key = key % 12;
+        let tonic = 60 + key as i32;
+        Ok(Self {
+            kind,
+            name: format!("{} ({})", kind.name(), NOTE_NAMES[key as usize]),
+            key,
+            degrees,
+            period: 1200.0,
+            mapping: KeyboardMapping::linear(tonic, 69, 440.0),
+        })
+    }
+
+    /// Scalaファイル（.scl、あれば.kbm）を読み込んで音律を作成
+    pub fn load_scala(scl_path: &str, kbm_path: Option<&str>) -> Result<Self, String> {
+        let scl = fs::read_to_string(scl_path).map_err(|e| format!("{}: {}", scl_path, e))?;
+        let kbm = kbm_path
+            .map(|path| fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e)))
+            .transpose()?;
+        Self::from_scala(&scl, kbm.as_deref())
+    }
+
+    /// Scala形式のテキストから音律を作成（.kbmがなければ音度0を平均律のC4に合わせる）
+    pub fn from_scala(scl: &str, kbm: Option<&str>) -> Result<Self, String> {
+        let (description, pitches) = parse_scl(scl)?;
+        let period = *pitches.last().unwrap();
+        if period <= 0.0 {
+            return Err("Scale period must be greater than 1/1".to_string());
+        }
+        let mapping = match kbm {
+            Some(text) => parse_kbm(text)?,
+            None => KeyboardMapping::linear(60, 60, midi_to_frequency(60, 440.0)),
+        };
+
+        let mut degrees = vec![0.0];
+        degrees.extend_from_slice(&pitches[..pitches.len() - 1]);
+        let temperament = Self {
+            kind: TemperamentKind::Scala,
+            name: if description.is_empty() {
+                format!("{}-note scale", degrees.len())
+            } else {
+                description
+            },
+            key: 0,
+            degrees,
+            period,
+            mapping,
+        };
+        if temperament
+            .key_cents(temperament.mapping.reference_note)
+            .is_none()
+        {
+            return Err("Keyboard mapping leaves the reference note unmapped".to_string());
+        }
+        Ok(temperament)
+    }
+
+    /// 音度の高さ（音度0からのセント、周期をまたぐ音度も扱う）
+    fn degree_cents(&self, degree: i64) -> f32 {
+        let n = self.degrees.len() as i64;
+        degree.div_euclid(n) as f32 * self.period + self.degrees[degree.rem_euclid(n) as usize]
+    }
+
+    /// 対応表の1周期の音程（セント）
+    fn mapping_period(&self) -> f32 {
+        match self.mapping.octave_degree {
+            0 => self.period,
+            degree => self.degree_cents(degree as i64),
+        }
+    }
+
+    /// 鍵盤の高さ（音度0の鍵盤からのセント、範囲外・割り当てのない鍵盤はNone）
+    fn key_cents(&self, key: i32) -> Option<f32> {
+        let mapping = &self.mapping;
+        if key < mapping.first_note || key > mapping.last_note {
+            return None;
+        }
+        let offset = key as i64 - mapping.middle_note as i64;
+        if mapping.size == 0 {
+            return Some(self.degree_cents(offset));
+        }
+        let size = mapping.size as i64;
+        let degree = (*mapping.keys.get(offset.rem_euclid(size) as usize)?)?;
+        Some(
+            offset.div_euclid(size) as f32 * self.mapping_period()
+                + self.degree_cents(degree as i64),
+        )
+    }
+
+    /// 鍵盤の周波数（基準周波数はA4の設定に合わせて比例させる）
+    pub fn key_frequency(&self, key: i32, a4: f32) -> Option<f32> {
+        let reference = self.key_cents(self.mapping.reference_note)?;
+        let cents = self.key_cents(key)?;
+        Some(self.mapping.reference_freq * a4 / 440.0 * ((cents - reference) / 1200.0).exp2())
+    }
+
+    /// 周波数に最も近い音度の音を求める
+    /// 戻り値: (音名, その音の周波数, セント値)
+    pub fn nearest_note(&self, freq: f32, a4: f32) -> (String, f32, f32) {
+        // 鍵盤1つあたりの平均の音程から近い鍵盤を見積もり、前後1周期分の鍵盤から選ぶ
+        let span = match self.mapping.size {
+            0 => self.degrees.len(),
+            size => size,
+        } as i32;
+        let step = self.mapping_period() / span as f32;
+        let reference_freq = self.mapping.reference_freq * a4 / 440.0;
+        let estimate = self.mapping.reference_note
+            + (1200.0 * (freq / reference_freq).log2() / step).round() as i32;
+
+        let nearest = (estimate.saturating_sub(span)..=estimate.saturating_add(span))
+            .filter_map(|key| self.key_frequency(key, a4).map(|f| (key, f)))
+            .min_by(|a, b| {
+                let diff_a = (freq / a.1).log2().abs();
+                let diff_b = (freq / b.1).log2().abs();
+                diff_a.partial_cmp(&diff_b).unwrap()
+            });
+        let Some((key, note_freq)) = nearest else {
+            // 対応表の範囲外は平均律で代用
+            return Self::default().nearest_note(freq, a4);
+        };
+
+        let cents = 1200.0 * (freq / note_freq).log2();
+        (self.note_name(key, note_freq, a4), note_freq, cents)
+    }
+
+    /// 1周期12音でオクターブを周期とする音律か（鍵盤番号をMIDIノートとみなせる）
+    fn is_twelve_tone(&self) -> bool {
+        let span = match self.mapping.size {
+            0 => self.degrees.len(),
+            size => size,
+        };
+        span == 12 && (self.mapping_period() - 1200.0).abs() < 1.0
+    }
+
+    /// 音名（12音の音律は鍵盤の音名、それ以外は最も近い12平均律の音名に^/vを付ける）
+    fn note_name(&self, key: i32, freq: f32, a4: f32) -> String {
+        if self.is_twelve_tone() {
+            return midi_note_name(key);
+        }
+        // 0.01セント単位に丸めて、4分音のようなちょうど中間の音は上の音を基準にする
+        let cents = (1200.0 * (freq / a4).log2() * 100.0).round() / 100.0;
+        let nearest = (cents / 100.0).round();
+        let deviation = cents - nearest * 100.0;
+        let mut name = midi_note_name(69 + nearest as i32);
+        if deviation >= MICROTONE_MARK_CENTS {
+            name.push('^');
+        } else if deviation <= -MICROTONE_MARK_CENTS {
+            name.push('v');
+        }
+        name
+    }
+}
+
+/// 主音から5度を積み重ねた12音（オクターブ内に収める）
+fn fifths_chain(fifth: f32) -> Vec<f32> {
+    FIFTHS_FROM_TONIC
+        .iter()
+        .map(|&n| (n as f32 * fifth).rem_euclid(1200.0))
+        .collect()
+}
+
+/// MIDIノート番号の音名（"A4" など）
+fn midi_note_name(midi: i32) -> String {
+    format!(
+        "{}{}",
+        NOTE_NAMES[midi.rem_euclid(12) as usize],
+        midi.div_euclid(12) - 1
+    )
+}
+
+/// MIDIノート番号の平均律での周波数
+fn midi_to_frequency(midi: i32, a4: f32) -> f32 {
+    a4 * ((midi - 69) as f32 / 12.0).exp2()
+}
+
+/// Scalaファイルのコメント以外の行
+fn scala_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().filter(|line| !line.starts_with('!'))
+}
+
+/// .sclを解析（戻り値: 説明, 音度1以降の高さ（セント、最後が周期））
+fn parse_scl(text: &str) -> Result<(String, Vec<f32>), String> {
+    let mut lines = scala_lines(text);
+    let description = lines
+        .next()
+        .ok_or("Scale file is empty")?
+        .trim()
+        .to_string();
+    let count: usize = lines
+        .next()
+        .and_then(|line| line.split_whitespace().next()?.parse().ok())
+        .ok_or("Invalid note count in scale file")?;
+    if count == 0 {
+        return Err("Scale must have at least one note".to_string());
+    }
+    let pitches = lines
+        .take(count)
+        .map(parse_scl_pitch)
+        .collect::<Result<Vec<f32>, String>>()?;
+    if pitches.len() != count {
+        return Err(format!(
+            "Scale file lists {} of {} notes",
+            pitches.len(),
+            count
+        ));
+    }
+    Ok((description, pitches))
+}
+
+/// .sclの音の高さ（小数点を含めばセント、それ以外は整数または分数の比）
+fn parse_scl_pitch(line: &str) -> Result<f32, String> {
+    let token = line
+        .split_whitespace()
+        .next()
+        .ok_or("Empty pitch line in scale file")?;
+    let invalid = || format!("Invalid pitch in scale file: {}", token);
+    if token.contains('.') {
+        return token.parse().map_err(|_| invalid());
+    }
+    let (numerator, denominator) = token.split_once('/').unwrap_or((token, "1"));
+    let numerator: f64 = numerator.parse().map_err(|_| invalid())?;
+    let denominator: f64 = denominator.parse().map_err(|_| invalid())?;
+    if numerator <= 0.0 || denominator <= 0.0 {
+        return Err(invalid());
+    }
+    Ok((1200.0 * (numerator / denominator).log2()) as f32)
+}
+
+/// .kbmを解析
+fn parse_kbm(text: &str) -> Result<KeyboardMapping, String> {
+    let mut lines = scala_lines(text).map(str::trim);
+    let mut field = |name: &str| {
+        lines
+            .next()
+            .and_then(|line| line.split_whitespace().next())
+            .map(str::to_string)
+            .ok_or_else(|| format!("Missing {} in keyboard mapping", name))
+    };
+    fn number<T: std::str::FromStr>(value: String, name: &str) -> Result<T, String> {
+        value
+            .parse()
+            .map_err(|_| format!("Invalid {} in keyboard mapping: {}", name, value))
+    }
+
+    let size: usize = number(field("map size")?, "map size")?;
+    let first_note = number(field("first note")?, "first note")?;
+    let last_note = number(field("last note")?, "last note")?;
+    let middle_note = number(field("middle note")?, "middle note")?;
+    let reference_note = number(field("reference note")?, "reference note")?;
+    let reference_freq: f32 = number(field("reference frequency")?, "reference frequency")?;
+    let octave_degree = number(field("octave degree")?, "octave degree")?;
+    if !(reference_freq.is_finite() && reference_freq > 0.0) {
+        return Err(format!(
+            "Invalid reference frequency in keyboard mapping: {}",
+            reference_freq
+        ));
+    }
+
+    // 記載のない鍵盤は割り当てなしとする
+    let mut keys = Vec::with_capacity(size);
+    for _ in 0..size {
+        keys.push(match field("key") {
+            Ok(value) if value == "x" => None,
+            Ok(value) => Some(number(value, "key")?),
+            Err(_) => None,
+        });
+    }
+
+    Ok(KeyboardMapping {
+        size,
+        first_note,
+        last_note,
+        middle_note,
+        reference_note,
+        reference_freq,
+        octave_degree,
+        keys,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_note(temperament: &Temperament, freq: f32, name: &str, cents: f32) {
+        let (note_name, _, note_cents) = temperament.nearest_note(freq, 440.0);
+        assert_eq!(note_name, name, "{} Hz", freq);
+        assert!(
+            (note_cents - cents).abs() < 0.05,
+            "{} Hz: {} != {}",
+            freq,
+            note_cents,
+            cents
+        );
+    }
+
+    #[test]
+    fn test_twelve_edo_matches_equal_temperament() {
+        let temperament = Temperament::default();
+        assert_note(&temperament, 440.0, "A4", 0.0);
+        assert_note(&temperament, 82.41, "E2", 0.07);
+        assert_note(&temperament, 330.0, "E4", 1.96);
+        // A4の設定に追従する
+        let (name, freq, _) = temperament.nearest_note(442.0, 442.0);
+        assert_eq!((name.as_str(), freq), ("A4", 442.0));
+    }
+
+    #[test]
+    fn test_edo_divisions() {
+        let edo31 = Temperament::equal(31);
+        // 31平均律の長3度（10音度）は純正長3度に近い
+        let third = edo31.key_frequency(69 + 10, 440.0).unwrap();
+        assert!((1200.0 * (third / 440.0).log2() - 387.1).abs() < 0.1);
+        assert_eq!(edo31.nearest_note(third, 440.0).0, "C#5");
+
+        // 24平均律の4分音には記号を付ける
+        let edo24 = Temperament::equal(24);
+        let quarter_sharp = 440.0 * (50.0_f32 / 1200.0).exp2();
+        assert_eq!(edo24.nearest_note(quarter_sharp, 440.0).0, "A#4v");
+        assert_eq!(Temperament::equal(19).degrees.len(), 19);
+    }
+
+    #[test]
+    fn test_twelve_tone_temperaments() {
+        // G基準の純正律: A4は基準ピッチのまま、主音のGはAから純正な全音（9/8）下、長3度（B）は純正
+        let just = Temperament::from_kind(TemperamentKind::Just, 12, 7).unwrap();
+        assert_note(&just, 440.0, "A4", 0.0);
+        let g = 440.0 * 8.0 / 9.0;
+        assert_note(&just, g, "G4", 0.0);
+        assert_note(&just, g * 5.0 / 4.0, "B4", 0.0);
+        assert_note(&just, 493.88, "B4", 17.59);
+
+        let pythagorean = Temperament::from_kind(TemperamentKind::Pythagorean, 12, 0).unwrap();
+        assert!((pythagorean.degrees[4] - 407.82).abs() < 0.01);
+        assert!((pythagorean.degrees[7] - 701.955).abs() < 0.01);
+
+        let meantone = Temperament::from_kind(TemperamentKind::Meantone, 12, 0).unwrap();
+        assert!((meantone.degrees[4] - 386.31).abs() < 0.01);
+        // どの主音でもA4は基準ピッチに一致する
+        for key in 0..12 {
+            let meantone = Temperament::from_kind(TemperamentKind::Meantone, 12, key).unwrap();
+            assert_eq!(
+                meantone.key_frequency(69, 415.0),
+                Some(415.0),
+                "key {}",
+                key
+            );
+            assert_eq!(meantone.nearest_note(415.0, 415.0).0, "A4");
+        }
+
+        let werckmeister = Temperament::from_kind(TemperamentKind::Werckmeister, 12, 0).unwrap();
+        assert_note(&werckmeister, 440.0, "A4", 0.0);
+        assert_note(&werckmeister, 261.626, "C4", -11.73);
+        assert!(Temperament::from_kind(TemperamentKind::Scala, 12, 0).is_err());
+    }
+
+    #[test]
+    fn test_scala_scale() {
+        let scl = "! meantone.scl\n!\n1/4-comma meantone\n 3\n!\n 5/4\n 696.578 fifth\n 2\n";
+        let temperament = Temperament::from_scala(scl, None).unwrap();
+        assert_eq!(temperament.name, "1/4-comma meantone");
+        assert_eq!(temperament.degrees.len(), 3);
+        assert!((temperament.degrees[1] - 386.31).abs() < 0.01);
+        assert_eq!(temperament.period, 1200.0);
+        // 音度0はC4、次の周期の音度1はE5
+        let c4 = temperament.key_frequency(60, 440.0).unwrap();
+        assert!((c4 - 261.626).abs() < 0.01);
+        let e5 = temperament.key_frequency(64, 440.0).unwrap();
+        assert!((e5 / c4 - 2.5).abs() < 1e-4);
+        assert_eq!(temperament.nearest_note(e5, 440.0).0, "E5");
+
+        assert!(Temperament::from_scala("Empty\n0\n", None).is_err());
+        assert!(Temperament::from_scala("Broken\n2\n5/4\n", None).is_err());
+        assert!(Temperament::from_scala("Broken\n1\n-3/2\n", None).is_err());
+    }
+
+    #[test]
+    fn test_scala_keyboard_mapping() {
+        // 12平均律の白鍵だけに割り当て、A4=432Hz
+        let scl =
+            "12-TET\n12\n100.\n200.\n300.\n400.\n500.\n600.\n700.\n800.\n900.\n1000.\n1100.\n2/1\n";
+        let kbm =
+            "! white keys\n12\n0\n127\n60\n69\n432.0\n12\n0\nx\n2\nx\n4\n5\nx\n7\nx\n9\nx\n11\n";
+        let temperament = Temperament::from_scala(scl, Some(kbm)).unwrap();
+        assert_eq!(temperament.key_frequency(69, 440.0), Some(432.0));
+        assert_eq!(temperament.key_frequency(61, 440.0), None);
+        assert_eq!(temperament.key_frequency(128, 440.0), None);
+        // 黒鍵の高さは近い白鍵として扱う
+        assert_note(&temperament, 432.0 * (80.0_f32 / 1200.0).exp2(), "A4", 80.0);
+
+        let unmapped_reference = "1\n0\n127\n60\n61\n440.0\n1\nx\n";
+        assert!(Temperament::from_scala(scl, Some(unmapped_reference)).is_err());
+        assert_eq!(
+            TemperamentKind::from_name(TemperamentKind::Werckmeister.name()),
+            Some(TemperamentKind::Werckmeister)
+        );
+    }
+}
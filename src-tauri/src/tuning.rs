@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+
+/// 音名（シャープ表記）
+pub const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// チューニングの最大弦数
+pub const MAX_TUNING_STRINGS: usize = 12;
+
+/// 標準チューニングの名前
+pub const STANDARD_TUNING_NAME: &str = "Standard";
+
+/// 弦ごとのオフセットの上限（セント）
+pub const MAX_STRING_OFFSET_CENTS: f32 = 50.0;
+
+/// 弦の目標音（"E2" のような音名、または A4=440Hz 基準の周波数）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum StringPitch {
+    Frequency(f32),
+    Note(String),
+}
+
+impl StringPitch {
+    /// A4=440Hz 基準の周波数に変換
+    pub fn frequency(&self) -> Option<f32> {
+        match self {
+            Self::Frequency(freq) => Some(*freq),
+            Self::Note(name) => note_name_to_frequency(name, 440.0),
+        }
+    }
+}
+
+/// チューニング（低音弦から順に各弦の目標音を持つ）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Tuning {
+    pub name: String,
+    pub strings: Vec<StringPitch>,
+}
+
+impl Tuning {
+    /// 音名のリストからチューニングを作成
+    pub fn from_notes(name: &str, notes: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            strings: notes
+                .iter()
+                .map(|n| StringPitch::Note(n.to_string()))
+                .collect(),
+        }
+    }
+
+    /// 各弦の目標周波数（A4=440Hz 基準、解釈できない弦は除外）
+    /// 解析では移調・音律を反映したPitchReference::string_targetsを使う
+    #[cfg(test)]
+    pub fn frequencies(&self) -> Vec<f32> {
+        self.strings
+            .iter()
+            .filter_map(StringPitch::frequency)
+            .collect()
+    }
+
+    /// ユーザー定義チューニングとして妥当か検証
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Tuning name must not be empty".to_string());
+        }
+        if self.strings.is_empty() || self.strings.len() > MAX_TUNING_STRINGS {
+            return Err(format!(
+                "Tuning must have between 1 and {} strings",
+                MAX_TUNING_STRINGS
+            ));
+        }
+        for string in &self.strings {
+            match string.frequency() {
+                Some(freq) if (20.0..=5000.0).contains(&freq) => {}
+                _ => return Err(format!("Invalid string pitch: {:?}", string)),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 組み込みのチューニングプリセット
+pub fn builtin_tunings() -> Vec<Tuning> {
+    vec![
+        Tuning::from_notes(STANDARD_TUNING_NAME, &["E2", "A2", "D3", "G3", "B3", "E4"]),
+        Tuning::from_notes("Drop D", &["D2", "A2", "D3", "G3", "B3", "E4"]),
+        Tuning::from_notes("DADGAD", &["D2", "A2", "D3", "G3", "A3", "D4"]),
+        Tuning::from_notes("Open G", &["D2", "G2", "D3", "G3", "B3", "D4"]),
+        Tuning::from_notes("Open D", &["D2", "A2", "D3", "F#3", "A3", "D4"]),
+        Tuning::from_notes("Open E", &["E2", "B2", "E3", "G#3", "B3", "E4"]),
+        Tuning::from_notes("Open C", &["C2", "G2", "C3", "G3", "C4", "E4"]),
+        Tuning::from_notes(
+            "Half Step Down",
+            &["D#2", "G#2", "C#3", "F#3", "A#3", "D#4"],
+        ),
+        Tuning::from_notes("Full Step Down", &["D2", "G2", "C3", "F3", "A3", "D4"]),
+        Tuning::from_notes(
+            "7-String Standard",
+            &["B1", "E2", "A2", "D3", "G3", "B3", "E4"],
+        ),
+        Tuning::from_notes(
+            "8-String Standard",
+            &["F#1", "B1", "E2", "A2", "D3", "G3", "B3", "E4"],
+        ),
+        Tuning::from_notes("Baritone Standard", &["B1", "E2", "A2", "D3", "F#3", "B3"]),
+        Tuning::from_notes("Ukulele Standard", &["G4", "C4", "E4", "A4"]),
+        Tuning::from_notes("Mandolin Standard", &["G3", "D4", "A4", "E5"]),
+        Tuning::from_notes("Violin Standard", &["G3", "D4", "A4", "E5"]),
+        Tuning::from_notes("Bass Standard", &["E1", "A1", "D2", "G2"]),
+        Tuning::from_notes("5-String Bass Standard", &["B0", "E1", "A1", "D2", "G2"]),
+        Tuning::from_notes(
+            "6-String Bass Standard",
+            &["B0", "E1", "A1", "D2", "G2", "C3"],
+        ),
+    ]
+}
+
+/// 標準チューニング
+pub fn standard_tuning() -> Tuning {
+    builtin_tunings().swap_remove(0)
+}
+
+/// 組み込みプリセットの名前かどうか
+pub fn is_builtin_tuning(name: &str) -> bool {
+    builtin_tunings().iter().any(|t| t.name == name)
+}
+
+/// プリセットとユーザー定義から名前でチューニングを検索
+pub fn find_tuning(name: &str, user_tunings: &[Tuning]) -> Option<Tuning> {
+    builtin_tunings()
+        .into_iter()
+        .chain(user_tunings.iter().cloned())
+        .find(|t| t.name == name)
+}
+
+/// 弦ごとのセントオフセット表（Buzz Feitenなどの補正チューニング用）
+/// チューニングの弦の順に並べ、表より弦が多い場合は残りの弦のオフセットを0とする
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OffsetTable {
+    pub name: String,
+    pub offsets: Vec<f32>,
+}
+
+impl OffsetTable {
+    pub fn new(name: &str, offsets: &[f32]) -> Self {
+        Self {
+            name: name.to_string(),
+            offsets: offsets.to_vec(),
+        }
+    }
+
+    /// 弦のオフセット（セント）
+    pub fn offset(&self, string_index: usize) -> f32 {
+        self.offsets.get(string_index).copied().unwrap_or(0.0)
+    }
+
+    /// ユーザー定義のオフセット表として妥当か検証
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Offset table name must not be empty".to_string());
+        }
+        if self.offsets.is_empty() || self.offsets.len() > MAX_TUNING_STRINGS {
+            return Err(format!(
+                "Offset table must have between 1 and {} strings",
+                MAX_TUNING_STRINGS
+            ));
+        }
+        if let Some(offset) = self
+            .offsets
+            .iter()
+            .find(|o| !o.is_finite() || o.abs() > MAX_STRING_OFFSET_CENTS)
+        {
+            return Err(format!("Invalid string offset: {} cents", offset));
+        }
+        Ok(())
+    }
+}
+
+/// 組み込みのオフセット表（6弦ギター用に一般に公開されている値）
+pub fn builtin_offset_tables() -> Vec<OffsetTable> {
+    vec![
+        OffsetTable::new("Buzz Feiten", &[-2.0, 0.0, -2.0, -3.0, -1.0, -2.0]),
+        OffsetTable::new("Earvana", &[0.0, 0.0, 0.0, -1.0, -1.0, 0.0]),
+    ]
+}
+
+/// 組み込みのオフセット表の名前かどうか
+pub fn is_builtin_offset_table(name: &str) -> bool {
+    builtin_offset_tables().iter().any(|t| t.name == name)
+}
+
+/// 組み込みとユーザー定義から名前でオフセット表を検索
+pub fn find_offset_table(name: &str, user_tables: &[OffsetTable]) -> Option<OffsetTable> {
+    builtin_offset_tables()
+        .into_iter()
+        .chain(user_tables.iter().cloned())
+        .find(|t| t.name == name)
+}
+
+/// 音名（"E2", "C#3", "Bb1" など）をMIDIノート番号に変換
+pub fn parse_note_name(name: &str) -> Option<i32> {
+    let name = name.trim();
+    let mut chars = name.chars();
+    let letter = chars.next()?.to_ascii_uppercase();
+    let base = match letter {
+        'C' => 0,
+        'D' => 2,
+        'E' => 4,
+        'F' => 5,
+        'G' => 7,
+        'A' => 9,
+        'B' => 11,
+        _ => return None,
+    };
+
+    let rest = chars.as_str();
+    let (accidental, octave_str) = if let Some(r) = rest.strip_prefix('#') {
+        (1, r)
+    } else if let Some(r) = rest.strip_prefix('b') {
+        (-1, r)
+    } else {
+        (0, rest)
+    };
+    let octave: i32 = octave_str.parse().ok()?;
+
+    // C4 = MIDIノート60
+    Some((octave + 1) * 12 + base + accidental)
+}
+
+/// 音名を指定したA4基準の周波数に変換
+pub fn note_name_to_frequency(name: &str, a4: f32) -> Option<f32> {
+    let midi = parse_note_name(name)?;
+    Some(a4 * 2.0_f32.powf((midi - 69) as f32 / 12.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_note_name() {
+        assert_eq!(parse_note_name("A4"), Some(69));
+        assert_eq!(parse_note_name("E2"), Some(40));
+        assert_eq!(parse_note_name("C#3"), Some(49));
+        assert_eq!(parse_note_name("Bb1"), Some(34));
+        assert_eq!(parse_note_name("H2"), None);
+        assert_eq!(parse_note_name("E"), None);
+    }
+
+    #[test]
+    fn test_standard_tuning_frequencies() {
+        let freqs = standard_tuning().frequencies();
+        let expected = [82.41, 110.0, 146.83, 196.0, 246.94, 329.63];
+        assert_eq!(freqs.len(), expected.len());
+        for (f, e) in freqs.iter().zip(expected) {
+            assert!((f - e).abs() < 0.01, "{} != {}", f, e);
+        }
+    }
+
+    #[test]
+    fn test_string_pitch_deserialize() {
+        let tuning: Tuning =
+            serde_json::from_str(r#"{"name":"Custom","strings":["D2",110.0]}"#).unwrap();
+        assert_eq!(tuning.strings[0], StringPitch::Note("D2".to_string()));
+        assert_eq!(tuning.strings[1], StringPitch::Frequency(110.0));
+        assert!(tuning.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_pitch() {
+        let tuning = Tuning::from_notes("Broken", &["X2"]);
+        assert!(tuning.validate().is_err());
+    }
+
+    #[test]
+    fn test_offset_table() {
+        let table = find_offset_table("Buzz Feiten", &[]).unwrap();
+        assert_eq!(table.offset(3), -3.0);
+        // 表にない弦はオフセットなし
+        assert_eq!(table.offset(6), 0.0);
+
+        let custom = OffsetTable::new("Sweetened", &[1.5, -0.5]);
+        assert!(custom.validate().is_ok());
+        let user_tables = vec![custom.clone()];
+        assert_eq!(find_offset_table("Sweetened", &user_tables), Some(custom));
+        assert!(OffsetTable::new("Too far", &[60.0]).validate().is_err());
+        assert!(OffsetTable::new(" ", &[1.0]).validate().is_err());
+    }
+}
@@ -2,8 +2,6 @@ export { useAudioDevice, type Settings } from "./useAudioDevice";
 export {
   GUITAR_NOTES,
   NOTE_NAMES,
-  getEffectiveA4,
-  getGuitarNotes,
   TUNING_SHIFTS,
   DROP_TUNINGS,
 } from "./constants";
@@ -1,22 +1,72 @@
 import { ref, onMounted, watch, computed } from "vue";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
-import type { ChannelMode, PitchMode, NoteInfo, NoteInfoPayload, TuningStatus } from "../types";
+import type {
+  ChannelMode,
+  HumFilter,
+  Instrument,
+  NoiseCalibratedPayload,
+  NoiseReduction,
+  PitchDetector,
+  PitchMode,
+  PitchPreset,
+  PitchReference,
+  PitchSmoothing,
+  StringTarget,
+  NoteInfo,
+  NoteInfoPayload,
+  PeakInterpolator,
+  PeakRefinement,
+  StreamState,
+  TemperamentKind,
+  TunerMode,
+  OffsetTable,
+  Tuning,
+  TuningStatus,
+  WindowFunction,
+} from "../types";
+import { MAX_CUSTOM_PITCH, MAX_TUNING_SHIFT_CENTS, MIN_CUSTOM_PITCH } from "./constants";
 
 export type ThemeMode = "system" | "light" | "dark";
 export type Settings = {
   device_name?: string;
   threshold?: number;
+  noise_gate?: number;
+  noise_reduction?: NoiseReduction;
   channel_mode?: number;
   tray_icon_mode?: number;
   pitch_mode?: PitchMode;
   custom_pitch?: number;
-  tuning_shift?: number;
+  tuning_shift?: number; // 半音（小数可）
+  capo?: number;
   drop_tuning_enabled?: boolean;
   drop_tuning_note?: string;
   theme_mode?: ThemeMode;
   always_on_top?: boolean;
   locale?: string;
+  pitch_detector?: PitchDetector;
+  window_function?: WindowFunction;
+  kaiser_beta?: number;
+  peak_interpolator?: PeakInterpolator;
+  peak_refinement?: PeakRefinement;
+  hum_filter?: HumFilter;
+  onset_skip_ms?: number;
+  pitch_smoothing?: PitchSmoothing;
+  smoothing_ema_alpha?: number;
+  kalman_process_noise?: number;
+  kalman_measurement_noise?: number;
+  hop_size?: number;
+  tuner_mode?: TunerMode;
+  instrument?: Instrument;
+  tuning?: string;
+  user_tunings?: Tuning[];
+  offset_table?: string | null;
+  user_offset_tables?: OffsetTable[];
+  temperament?: TemperamentKind;
+  temperament_divisions?: number;
+  temperament_key?: string;
+  scala_file?: string;
+  scala_keyboard_file?: string;
 };
 
 /**
@@ -32,6 +82,8 @@ export function useAudioDevice() {
   const rawFrequency = ref<number | null>(null);
   const threshold = ref(2.0);
   const inputLevel = ref(0);
+  const streamState = ref<StreamState>("active");
+  const calibratingNoise = ref(false);
   const channelMode = ref<ChannelMode>(1); // 0=左, 1=右, 2=平均
   const noteInfo = ref<NoteInfo>({ name: "-", cent: 0, targetFreq: 0 });
   const tuningStatus = ref<TuningStatus>("off");
@@ -44,9 +96,12 @@ export function useAudioDevice() {
   // 新しい設定項目
   const pitchMode = ref<PitchMode>("standard");
   const customPitch = ref(440);
-  const tuningShift = ref(-1);
+  const tuningShiftCents = ref(-100);
+  const pitchPresets = ref<PitchPreset[]>([]);
+  const capo = ref(0);
   const dropEnabled = ref(false);
   const dropNote = ref("D");
+  const stringTargets = ref<StringTarget[]>([]);
 
   async function updateThreshold(value: number) {
     threshold.value = value;
@@ -54,12 +109,33 @@ export function useAudioDevice() {
     await saveSettings({ threshold: value });
   }
 
+  async function calibrateNoise(durationSecs?: number) {
+    calibratingNoise.value = true;
+    try {
+      await invoke("calibrate_noise", { durationSecs });
+    } catch (e: unknown) {
+      calibratingNoise.value = false;
+      throw e;
+    }
+  }
+
   async function updateChannelMode(mode: ChannelMode) {
     channelMode.value = mode;
     await invoke("set_channel_mode", { mode });
     await saveSettings({ channel_mode: mode });
   }
 
+  /** 各弦の目標（音名・周波数）をバックエンドから取り直す */
+  async function refreshStringTargets() {
+    const reference = await invoke<PitchReference>("get_pitch_reference");
+    stringTargets.value = reference.stringTargets;
+  }
+
+  /** プリセットの基準ピッチに切り替える（保存と反映はpitch_reference_changedで受け取る） */
+  async function applyPitchPreset(id: string) {
+    await invoke("set_pitch_preset", { id });
+  }
+
   async function startListening(device: string) {
     try {
       listenStatus.value = "Starting...";
@@ -85,24 +161,36 @@ export function useAudioDevice() {
   watch(pitchMode, async (mode) => {
     const modeVal = mode === "standard" ? 0 : mode === "custom" ? 1 : 2;
     await invoke("set_pitch_mode", { mode: modeVal });
+    await refreshStringTargets();
     await saveSettings({ pitch_mode: mode });
   });
 
   watch(customPitch, async (pitch) => {
-    if (pitch >= 438 && pitch <= 445) {
+    if (pitch >= MIN_CUSTOM_PITCH && pitch <= MAX_CUSTOM_PITCH) {
       await invoke("set_custom_pitch", { pitch: Number(pitch) });
+      await refreshStringTargets();
       await saveSettings({ custom_pitch: Number(pitch) });
     }
   });
 
-  watch(tuningShift, async (shift) => {
-    await invoke("set_tuning_shift", { semitones: Number(shift) });
-    await saveSettings({ tuning_shift: Number(shift) });
+  watch(tuningShiftCents, async (cents) => {
+    if (Math.abs(cents) <= MAX_TUNING_SHIFT_CENTS) {
+      await invoke("set_tuning_shift_cents", { cents: Number(cents) });
+      await refreshStringTargets();
+      await saveSettings({ tuning_shift: Number(cents) / 100 });
+    }
+  });
+
+  watch(capo, async (fret) => {
+    await invoke("set_capo", { fret: Number(fret) });
+    await refreshStringTargets();
+    await saveSettings({ capo: Number(fret) });
   });
 
   watch([dropEnabled, dropNote], async ([enabled, note]) => {
     const noteVal = note === "D" ? 0 : note === "C#" ? 1 : note === "C" ? 2 : 3;
     await invoke("set_drop_tuning", { enabled, note: noteVal });
+    await refreshStringTargets();
     await saveSettings({
       drop_tuning_enabled: enabled,
       drop_tuning_note: note,
@@ -135,7 +223,9 @@ export function useAudioDevice() {
       // 新しい設定の復元
       if (settings.pitch_mode) pitchMode.value = settings.pitch_mode;
       if (settings.custom_pitch) customPitch.value = settings.custom_pitch;
-      if (settings.tuning_shift) tuningShift.value = settings.tuning_shift;
+      if (typeof settings.tuning_shift === "number")
+        tuningShiftCents.value = settings.tuning_shift * 100;
+      if (typeof settings.capo === "number") capo.value = settings.capo;
       if (typeof settings.drop_tuning_enabled === "boolean")
         dropEnabled.value = settings.drop_tuning_enabled;
       if (settings.drop_tuning_note) dropNote.value = settings.drop_tuning_note;
@@ -144,11 +234,15 @@ export function useAudioDevice() {
       const modeVal = pitchMode.value === "standard" ? 0 : pitchMode.value === "custom" ? 1 : 2;
       await invoke("set_pitch_mode", { mode: modeVal });
       await invoke("set_custom_pitch", { pitch: Number(customPitch.value) });
-      await invoke("set_tuning_shift", { semitones: Number(tuningShift.value) });
+      await invoke("set_tuning_shift_cents", { cents: Number(tuningShiftCents.value) });
+      await invoke("set_capo", { fret: Number(capo.value) });
       const noteVal =
         dropNote.value === "D" ? 0 : dropNote.value === "C#" ? 1 : dropNote.value === "C" ? 2 : 3;
       await invoke("set_drop_tuning", { enabled: !!dropEnabled.value, note: noteVal });
 
+      await refreshStringTargets();
+      pitchPresets.value = await invoke<PitchPreset[]>("get_pitch_presets");
+
       if (settings.device_name && devices.value.includes(settings.device_name)) {
         selectedDevice.value = settings.device_name;
       } else if (devices.value.length > 0) {
@@ -191,6 +285,27 @@ export function useAudioDevice() {
           tuningStatus.value = "off";
         }
       });
+      listen("stream_state", (event) => {
+        if (event.payload === "active" || event.payload === "stalled") {
+          streamState.value = event.payload;
+        }
+      });
+      listen("noise_calibrated", async (event) => {
+        const payload = event.payload as NoiseCalibratedPayload | undefined;
+        calibratingNoise.value = false;
+        if (!payload) return;
+        threshold.value = payload.thresholdRatio;
+        await saveSettings({ threshold: payload.thresholdRatio, noise_gate: payload.gate });
+      });
+      listen("pitch_reference_changed", (event) => {
+        const payload = event.payload as PitchReference | undefined;
+        if (!payload) return;
+        pitchMode.value = payload.mode;
+        customPitch.value = payload.customPitch;
+        tuningShiftCents.value = payload.shiftCents;
+        capo.value = payload.capo;
+        stringTargets.value = payload.stringTargets;
+      });
       listen("reset", () => {
         // 状態を初期化
         frequency.value = null;
@@ -221,16 +336,23 @@ export function useAudioDevice() {
     rawFrequency,
     threshold,
     inputLevel,
+    streamState,
+    calibratingNoise,
     channelMode,
     pitchMode,
     customPitch,
-    tuningShift,
+    tuningShiftCents,
+    pitchPresets,
+    capo,
     dropEnabled,
     dropNote,
+    stringTargets,
     noteInfo,
     tuningStatus,
     centDisplay,
     updateThreshold,
+    calibrateNoise,
+    applyPitchPreset,
     updateChannelMode,
     saveSettings,
   };
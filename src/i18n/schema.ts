@@ -1,6 +1,8 @@
 /** i18n message schema type definition */
 export interface MessageSchema {
   tuningShift: {
+    wholeStepUp: string;
+    halfStepUp: string;
     halfStepDown: string;
     wholeStepDown: string;
     oneHalfStepDown: string;
@@ -28,6 +30,7 @@ export interface MessageSchema {
     sensitivityHigh: string;
     sensitivityLow: string;
     referencePitch: string;
+    capo: string;
     dropTuning: string;
     trayIcon: string;
     theme: string;
@@ -45,7 +48,26 @@ export interface MessageSchema {
     customHint: string;
     shift: string;
     hz: string;
+    preset: string;
     errorRange: string;
+    cents: string;
+    shiftHint: string;
+    shiftErrorRange: string;
+  };
+  capo: {
+    position: string;
+    none: string;
+    fret: string;
+    helpText: string;
+  };
+  pitchPreset: {
+    a415: string;
+    a430: string;
+    a432: string;
+    a440: string;
+    a442: string;
+    a443: string;
+    a466: string;
   };
   trayIcon: {
     indicatorOnly: string;
@@ -14,6 +14,41 @@ export interface NoteInfo {
 /** バックエンドから送られてくる音名イベント */
 export interface NoteInfoPayload extends NoteInfo {
   tuningStatus: TuningStatus;
+  /** 検出の信頼度（0.0〜1.0） */
+  confidence: number;
+  /** ピークとノイズレベルの比（スペクトルを使わない検出器ではnull） */
+  peakToNoise: number | null;
+  /** 検出時のチューナーモード */
+  mode: TunerMode;
+  /** 解析したフレームの終端（ストリーム開始からの累計フレーム数） */
+  samplePosition: number;
+  /** 解析に使った窓長（サンプル数） */
+  windowSize: number;
+  /** 平滑化前の検出周波数 */
+  rawFrequency: number;
+  /** 目標とした弦のインデックス（チューニングの弦の順、クロマチックモードではnull） */
+  stringIndex: number | null;
+  sampleRate: number;
+  /** 解析に使ったFFT長（スペクトルを使わない検出器ではnull） */
+  fftSize: number | null;
+}
+
+/** 最後の検出結果（get_last_detectionの戻り値） */
+export interface LastDetection {
+  noteName: string;
+  /** 平滑化後の周波数 */
+  frequency: number;
+  cents: number;
+  targetFreq: number;
+  rawFrequency: number;
+  confidence: number;
+  peakToNoise: number | null;
+  stringIndex: number | null;
+  sampleRate: number;
+  fftSize: number | null;
+  windowSize: number;
+  mode: TunerMode;
+  samplePosition: number;
 }
 
 /** チューニング状態 */
@@ -27,3 +62,179 @@ export type PitchMode = "standard" | "custom" | "shift";
 
 /** ドロップチューニング音名 */
 export type DropTuningNote = "D" | "C#" | "C" | "B";
+
+/** ピッチ検出アルゴリズム */
+export type PitchDetector = "fft" | "yin" | "mcleod";
+
+/** FFT解析の窓関数 */
+export type WindowFunction = "blackman_harris" | "hann" | "hamming" | "kaiser" | "flat_top";
+
+/** ピークbinの補間方法 */
+export type PeakInterpolator = "gaussian" | "parabolic" | "quinn2" | "jain";
+
+/** ピーク周波数の精密化方法 */
+export type PeakRefinement = "interpolation" | "zoom";
+
+/** 電源ハム除去の前処理 */
+export type HumFilter = "off" | "auto" | "50hz" | "60hz";
+
+/** 検出した周波数の平滑化方法 */
+export type PitchSmoothing = "median" | "ema" | "kalman";
+
+/** スペクトル上のノイズ除去（calibrate_noise のノイズプロファイルを使う） */
+export type NoiseReduction = "off" | "spectral_subtraction" | "wiener";
+
+/** チューナーモード */
+export type TunerMode = "guitar" | "chromatic" | "strum" | "strobe";
+
+/** strum イベントの弦ごとの検出結果 */
+export interface StringReading {
+  /** 弦番号（1 = 最高音弦） */
+  string: number;
+  detected_freq: number | null;
+  cents: number | null;
+  confidence: number;
+}
+
+/** 弦の目標音（音名 または A4=440Hz 基準の周波数） */
+export type StringPitch = string | number;
+
+/** チューニング（低音弦から順） */
+export interface Tuning {
+  name: string;
+  strings: StringPitch[];
+}
+
+/** get_tunings の戻り値 */
+export interface TuningEntry extends Tuning {
+  builtin: boolean;
+}
+
+/** 弦ごとのセントオフセット表（チューニングの弦の順） */
+export interface OffsetTable {
+  name: string;
+  offsets: number[];
+}
+
+/** get_offset_tables の戻り値 */
+export interface OffsetTableEntry extends OffsetTable {
+  builtin: boolean;
+}
+
+/** 音律の種類 */
+export type TemperamentKind =
+  | "equal"
+  | "just"
+  | "pythagorean"
+  | "meantone"
+  | "werckmeister"
+  | "scala";
+
+/** get_temperament / load_scala_file の戻り値 */
+export interface TemperamentInfo {
+  kind: TemperamentKind;
+  name: string;
+  /** 主音（C=0〜B=11、12音の音律のみ） */
+  key: number;
+  /** 各音度の音度0からのセント */
+  degrees: number[];
+  /** 周期（セント） */
+  period: number;
+}
+
+/** 弦の目標（バックエンドが音律・基準ピッチ・オフセットから求めたもの） */
+export interface StringTarget {
+  /** チューニングの弦の順の位置（stringIndexと同じ番号） */
+  index: number;
+  name: string;
+  /** オフセットを加えた目標周波数（Hz） */
+  frequency: number;
+}
+
+/** 基準ピッチのプリセット（get_pitch_presetsの要素） */
+export interface PitchPreset {
+  /** 識別子（set_pitch_presetと翻訳キーに使う） */
+  id: string;
+  /** 表示名（英語、翻訳がなければこれを使う） */
+  name: string;
+  a4: number;
+}
+
+/** get_pitch_reference の戻り値（音名・目標・セント値の計算の基準） */
+export interface PitchReference {
+  mode: PitchMode;
+  customPitch: number;
+  /** A4=440Hzからのシフト（セント） */
+  shiftCents: number;
+  /** カポの位置（フレット、0=なし） */
+  capo: number;
+  offsetTable: OffsetTable | null;
+  /** 音名の基準のA4 */
+  noteA4: number;
+  /** 目標周波数の基準のA4 */
+  targetA4: number;
+  /** 目標の移調量（半音） */
+  transposition: number;
+  /** 現在の楽器・チューニングの各弦の目標 */
+  stringTargets: StringTarget[];
+}
+
+/** 楽器 */
+export type Instrument =
+  | "guitar"
+  | "guitar7"
+  | "guitar8"
+  | "baritone"
+  | "ukulele"
+  | "mandolin"
+  | "violin"
+  | "bass"
+  | "bass5"
+  | "bass6";
+
+/** get_instruments の戻り値 */
+export interface InstrumentInfo {
+  id: number;
+  name: Instrument;
+  string_count: number;
+  default_tuning: Tuning;
+  min_freq: number;
+  max_freq: number;
+  tolerance: number;
+}
+
+/** strobe イベント */
+export interface StrobePayload {
+  name: string;
+  frequency: number;
+  targetFreq: number;
+  cent: number;
+  /** 目標周波数に対する位相速度（rad/s、正なら高い） */
+  phaseVelocity: number;
+  confidence: number;
+  samplePosition: number;
+}
+
+/** strum イベント */
+export interface StrumPayload {
+  samplePosition: number;
+  strings: StringReading[];
+}
+
+/** 入力ストリームの状態（stream_state イベント） */
+export type StreamState = "active" | "stalled";
+
+/** onset イベント（撥弦の検出） */
+export interface OnsetPayload {
+  samplePosition: number;
+}
+
+/** noise_calibrated イベント */
+export interface NoiseCalibratedPayload {
+  /** 信号レベルのゲート（RMS） */
+  gate: number;
+  /** ピークとノイズレベルの比率の閾値 */
+  thresholdRatio: number;
+  /** 測定したノイズのRMS（dB） */
+  noiseLevel: number;
+}